@@ -0,0 +1,103 @@
+use std::any::Any;
+
+use thiserror::Error;
+
+use crate::lifecycle::ChannelId;
+use crate::message_set::{MessageSet, MessageSetSender, MsgAndReplaySender};
+use crate::reply::{reply_channel, ReplyFuture, ReplyTo};
+use crate::sync_wrapper::SyncWrapper;
+
+pub type AnyMessage = Box<dyn Any + Send>;
+
+/// A message or replay given back by an [`AnySendError`].
+pub struct AnyValue(SyncWrapper<AnyMessage>);
+
+impl AnyValue {
+    pub(crate) fn new(value: AnyMessage) -> Self {
+        Self(SyncWrapper::new(value))
+    }
+
+    pub fn into_inner(self) -> AnyMessage {
+        self.0.into_inner()
+    }
+
+    pub fn downcast<T: 'static>(self) -> Result<T, Self> {
+        self.into_inner()
+            .downcast()
+            .map(|value| *value)
+            .map_err(Self::new)
+    }
+}
+
+impl std::fmt::Debug for AnyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AnyValue")
+    }
+}
+
+/// Implemented by `#[msg_set]` for every message set.
+pub trait MessageSetAny: MessageSet {
+    /// Downcasts `msg` to one of the messages of the set, giving it back if it is none of them.
+    fn downcast_item(
+        msg: AnyMessage,
+        reply_to: ReplyTo<AnyMessage>,
+    ) -> Result<MsgAndReplaySender<Self>, AnyMessage>;
+}
+
+#[derive(Error, Debug)]
+pub enum AnySendError {
+    /// The message is not part of the set, it is given back.
+    #[error("UnsupportedMessage")]
+    UnsupportedMessage(AnyValue),
+    #[error("Disconnected")]
+    Disconnected,
+    /// The replay is not of the requested type, it is given back.
+    #[error("WrongReplyType")]
+    WrongReplyType(AnyValue),
+    #[error("ReplyDropped")]
+    ReplyDropped,
+}
+
+/// An object-safe sender of any message set, taking messages as [`AnyMessage`].
+pub trait AnySender: Send + Sync {
+    fn send_any(&self, msg: AnyMessage) -> Result<ReplyFuture<AnyMessage>, AnySendError>;
+
+    fn id(&self) -> ChannelId;
+}
+
+impl dyn AnySender {
+    /// Sends `msg` and downcasts its replay to `R`.
+    pub async fn request<M, R>(&self, msg: M) -> Result<R, AnySendError>
+    where
+        M: Send + 'static,
+        R: 'static,
+    {
+        let replay = self
+            .send_any(Box::new(msg))?
+            .try_replay()
+            .await
+            .map_err(|_| AnySendError::ReplyDropped)?;
+        AnyValue::new(replay)
+            .downcast()
+            .map_err(AnySendError::WrongReplyType)
+    }
+}
+
+impl<MS> AnySender for MessageSetSender<MS>
+where
+    MS: MessageSetAny,
+{
+    fn send_any(&self, msg: AnyMessage) -> Result<ReplyFuture<AnyMessage>, AnySendError> {
+        let (reply_to, replay) = reply_channel();
+        let msg = MS::downcast_item(msg, reply_to)
+            .map_err(|msg| AnySendError::UnsupportedMessage(AnyValue::new(msg)))?;
+        self.sender
+            .send(msg)
+            .map_err(|_| AnySendError::Disconnected)?;
+        Ok(replay)
+    }
+
+    fn id(&self) -> ChannelId {
+        MessageSetSender::id(self)
+    }
+}
@@ -0,0 +1,224 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+use futures_util::future::join_all;
+use thiserror::Error;
+
+use crate::any::AnyValue;
+use crate::message_set::{MessageSetContains, MessageSetSender};
+use crate::reply::{reply_channel, ReplyTo};
+
+type AnyReplay = Box<dyn Any + Send>;
+type Filter<M> = Box<dyn Fn(&M) -> bool + Send + Sync>;
+
+struct Subscriber<M> {
+    id: u64,
+    filter: Option<Filter<M>>,
+    /// Returns `false` once the receiver of the subscriber stopped.
+    deliver: Box<dyn Fn(M, ReplyTo<AnyReplay>) -> bool + Send + Sync>,
+}
+
+type Topic<M> = Vec<Arc<Subscriber<M>>>;
+
+/// The reply of a subscriber that `Bus::publish_collect` could not collect.
+#[derive(Error, Debug)]
+pub enum BusReplyError {
+    /// The subscriber stopped before it replied.
+    #[error("Unavailable")]
+    Unavailable,
+    /// The replay is not of the requested type, it is given back.
+    #[error("WrongReplyType")]
+    WrongReplyType(AnyValue),
+}
+
+#[derive(Default)]
+struct BusInner {
+    topics: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+    next_id: AtomicU64,
+}
+
+impl BusInner {
+    fn topics(&self) -> MutexGuard<'_, HashMap<TypeId, Box<dyn Any + Send + Sync>>> {
+        self.topics.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn with_topic<M, T>(&self, f: impl FnOnce(&mut Topic<M>) -> T) -> T
+    where
+        M: 'static,
+    {
+        let mut topics = self.topics();
+        let topic = topics
+            .entry(TypeId::of::<M>())
+            .or_insert_with(|| Box::new(Topic::<M>::new()))
+            .downcast_mut()
+            .expect("topics are keyed by their message type");
+        f(topic)
+    }
+}
+
+/// Fans published messages out to every message set subscribed to their type.
+///
+/// Cloning the bus is cheap, the clones share their subscribers. Subscribed senders are held
+/// weakly, so subscribing does not keep a channel open.
+#[derive(Clone, Default)]
+pub struct Bus {
+    inner: Arc<BusInner>,
+}
+
+impl Bus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delivers every message of type `M` published on this bus to `sender`, until the returned
+    /// subscription is dropped.
+    pub fn subscribe<M, MS>(&self, sender: &MessageSetSender<MS>) -> Subscription
+    where
+        M: 'static,
+        MS: MessageSetContains<M>,
+    {
+        self.subscribe_inner(sender, None)
+    }
+
+    /// Like `subscribe`, but only delivers the messages `filter` returns `true` for.
+    pub fn subscribe_filtered<M, MS>(
+        &self,
+        sender: &MessageSetSender<MS>,
+        filter: impl Fn(&M) -> bool + Send + Sync + 'static,
+    ) -> Subscription
+    where
+        M: 'static,
+        MS: MessageSetContains<M>,
+    {
+        self.subscribe_inner(sender, Some(Box::new(filter)))
+    }
+
+    fn subscribe_inner<M, MS>(
+        &self,
+        sender: &MessageSetSender<MS>,
+        filter: Option<Filter<M>>,
+    ) -> Subscription
+    where
+        M: 'static,
+        MS: MessageSetContains<M>,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let weak_sender = sender.sender.downgrade();
+        let state = sender.state.clone();
+        let subscriber = Subscriber {
+            id,
+            filter,
+            deliver: Box::new(move |msg, reply_to: ReplyTo<AnyReplay>| {
+                let Some(sender) = weak_sender.upgrade() else {
+                    return false;
+                };
+                let sender = MessageSetSender {
+                    sender,
+                    state: state.clone(),
+                };
+                let reply_to = reply_to.map(|replay: MS::Replay| Box::new(replay) as AnyReplay);
+                sender.forward(msg, reply_to).is_ok()
+            }),
+        };
+        self.inner
+            .with_topic(|topic: &mut Topic<M>| topic.push(Arc::new(subscriber)));
+        Subscription {
+            bus: Arc::downgrade(&self.inner),
+            remove: remove_subscriber::<M>,
+            id,
+        }
+    }
+
+    /// Sends `msg` to every subscriber of its type, ignoring their replies.
+    ///
+    /// Returns the number of subscribers it was sent to.
+    pub fn publish<M>(&self, msg: M) -> usize
+    where
+        M: Clone + 'static,
+    {
+        self.deliver(msg, ReplyTo::discard).len()
+    }
+
+    /// Sends `msg` to every subscriber of its type and collects their replies of type `R`.
+    ///
+    /// A reply of another type, or of a subscriber that stopped before replying, is an error.
+    pub fn publish_collect<M, R>(
+        &self,
+        msg: M,
+    ) -> impl Future<Output = Vec<Result<R, BusReplyError>>>
+    where
+        M: Clone + 'static,
+        R: 'static,
+    {
+        let mut replays = vec![];
+        self.deliver(msg, || {
+            let (reply_to, replay) = reply_channel();
+            replays.push(replay.try_replay());
+            reply_to
+        });
+        async move {
+            join_all(replays)
+                .await
+                .into_iter()
+                .map(|replay| {
+                    let replay = replay.map_err(|_| BusReplyError::Unavailable)?;
+                    AnyValue::new(replay)
+                        .downcast()
+                        .map_err(BusReplyError::WrongReplyType)
+                })
+                .collect()
+        }
+    }
+
+    /// Returns the ids of the subscribers `msg` was sent to.
+    fn deliver<M>(&self, msg: M, mut reply_to: impl FnMut() -> ReplyTo<AnyReplay>) -> Vec<u64>
+    where
+        M: Clone + 'static,
+    {
+        // the filters and senders run without holding the lock
+        let subscribers = self.inner.with_topic(|topic: &mut Topic<M>| topic.clone());
+        let mut delivered = vec![];
+        let mut stopped = vec![];
+        for subscriber in subscribers {
+            if subscriber.filter.as_ref().is_some_and(|filter| !filter(&msg)) {
+                continue;
+            }
+            if (subscriber.deliver)(msg.clone(), reply_to()) {
+                delivered.push(subscriber.id);
+            } else {
+                stopped.push(subscriber.id);
+            }
+        }
+        if !stopped.is_empty() {
+            self.inner.with_topic(|topic: &mut Topic<M>| {
+                topic.retain(|subscriber| !stopped.contains(&subscriber.id))
+            });
+        }
+        delivered
+    }
+}
+
+fn remove_subscriber<M>(bus: &BusInner, id: u64)
+where
+    M: 'static,
+{
+    bus.with_topic(|topic: &mut Topic<M>| topic.retain(|subscriber| subscriber.id != id));
+}
+
+/// Unsubscribes from the [`Bus`] when dropped.
+pub struct Subscription {
+    bus: Weak<BusInner>,
+    remove: fn(&BusInner, u64),
+    id: u64,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(bus) = self.bus.upgrade() {
+            (self.remove)(&bus, self.id);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::lifecycle::{ChannelId, ChannelState};
+use crate::message_set::{MessageSet, MessageSetContains, MessageSetSender, MsgAndReplaySender};
+use crate::reply::ReplyTo;
+use crate::timer::{deadline_after, TimerHandle};
+
+/// Passed to the `Handle*WithContext` handlers together with each message, `R` is the replay of
+/// the handled message.
+pub struct Context<MS, R>
+where
+    MS: MessageSet,
+{
+    pub(crate) address: mpsc::WeakUnboundedSender<MsgAndReplaySender<MS>>,
+    pub(crate) state: Arc<ChannelState>,
+    pub(crate) replay_sender: Option<ReplyTo<R>>,
+}
+
+impl<MS, R> Context<MS, R>
+where
+    MS: MessageSet,
+{
+    /// The sender of the handled channel, `None` once every other sender was dropped.
+    pub fn address(&self) -> Option<MessageSetSender<MS>> {
+        self.address.upgrade().map(|sender| MessageSetSender {
+            sender,
+            state: self.state.clone(),
+        })
+    }
+
+    pub fn id(&self) -> ChannelId {
+        self.state.id
+    }
+
+    /// Publishes `event` to the subscribers of the set, returns how many there are.
+    pub fn publish(&self, event: MS::Event) -> usize {
+        self.state.events().publish(event)
+    }
+
+    /// Stops the receiver once the current message is handled, see `MessageSetReceiver::stop`.
+    pub fn stop(&self) {
+        self.state.stop_requested.store(true, Ordering::Release);
+    }
+
+    /// Like `MessageSetSender::send_after`, but the timer does not keep the channel open.
+    pub fn send_after<M>(&self, delay: Duration, msg: M) -> TimerHandle
+    where
+        MS: MessageSetContains<M>,
+    {
+        self.send_at(deadline_after(delay), msg)
+    }
+
+    pub fn send_at<M>(&self, deadline: Instant, msg: M) -> TimerHandle
+    where
+        MS: MessageSetContains<M>,
+    {
+        let msg = MS::into_item(msg);
+        let address = self.address.clone();
+        self.state.timers().once(deadline, move || {
+            if let Some(sender) = address.upgrade() {
+                let _ = sender.send((msg, ReplyTo::discard()));
+            }
+        })
+    }
+
+    pub fn send_interval<M, F>(&self, period: Duration, mut f: F) -> TimerHandle
+    where
+        MS: MessageSetContains<M>,
+        F: FnMut() -> M + Send + 'static,
+    {
+        let address = self.address.clone();
+        self.state.timers().interval(period, move || {
+            address.upgrade().is_some_and(|sender| {
+                sender
+                    .send((MS::into_item(f()), ReplyTo::discard()))
+                    .is_ok()
+            })
+        })
+    }
+
+    /// Takes the sender of the reply, so the handler can reply later, e.g. from another task.
+    ///
+    /// The handler should return `None` afterwards, its return value is not sent. The slot can
+    /// also be passed to `MessageSetSender::forward` of another set.
+    pub fn take_reply(&mut self) -> Option<ReplyTo<R>> {
+        self.replay_sender.take()
+    }
+
+    /// Replies now, unless the sender of the reply was taken.
+    pub fn reply(&mut self, replay: R) {
+        if let Some(replay_sender) = self.replay_sender.take() {
+            replay_sender.send(replay);
+        }
+    }
+
+    /// Takes the sender of the reply into the context of a handler whose replays are converted
+    /// with `f`, e.g. to pass a variant on to the handler of its message.
+    pub fn map_reply<T>(&mut self, f: impl FnOnce(T) -> R + Send + 'static) -> Context<MS, T>
+    where
+        R: 'static,
+    {
+        Context {
+            address: self.address.clone(),
+            state: self.state.clone(),
+            replay_sender: self.replay_sender.take().map(|replay_sender| replay_sender.map(f)),
+        }
+    }
+}
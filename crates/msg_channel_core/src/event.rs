@@ -0,0 +1,158 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::task::{Context, Poll, Waker};
+
+use futures_util::Stream;
+use thiserror::Error;
+
+/// How a subscriber buffers the events it did not receive yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventPolicy {
+    /// Keeps the newest `capacity` events. Like a lagging tokio broadcast receiver, a subscriber
+    /// that falls further behind gets an [`EventLagged`] with the number of skipped events.
+    Lagging(usize),
+    /// Keeps every event, however far the subscriber falls behind.
+    Unbounded,
+}
+
+impl Default for EventPolicy {
+    fn default() -> Self {
+        EventPolicy::Lagging(128)
+    }
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("subscriber lagged behind by {0} events")]
+pub struct EventLagged(pub u64);
+
+struct Queue<E> {
+    events: VecDeque<E>,
+    capacity: Option<usize>,
+    lagged: u64,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The events of a message set, created with `MessageSetSender::subscribe`.
+///
+/// It ends once the receiver of the set stopped and the buffered events were received.
+pub struct EventStream<E>(Arc<Mutex<Queue<E>>>);
+
+impl<E> Stream for EventStream<E> {
+    type Item = Result<E, EventLagged>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut queue = lock(&self.0);
+        if queue.lagged > 0 {
+            let lagged = std::mem::take(&mut queue.lagged);
+            return Poll::Ready(Some(Err(EventLagged(lagged))));
+        }
+        if let Some(event) = queue.events.pop_front() {
+            return Poll::Ready(Some(Ok(event)));
+        }
+        if queue.closed {
+            return Poll::Ready(None);
+        }
+        queue.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+struct Subscribers<E> {
+    closed: bool,
+    queues: Vec<Weak<Mutex<Queue<E>>>>,
+}
+
+pub(crate) struct EventHub<E> {
+    subscribers: Mutex<Subscribers<E>>,
+}
+
+impl<E> EventHub<E>
+where
+    E: Clone,
+{
+    pub(crate) fn subscribe(&self, policy: EventPolicy) -> EventStream<E> {
+        let mut subscribers = lock(&self.subscribers);
+        let queue = Arc::new(Mutex::new(Queue {
+            events: VecDeque::new(),
+            capacity: match policy {
+                EventPolicy::Lagging(capacity) => Some(capacity.max(1)),
+                EventPolicy::Unbounded => None,
+            },
+            lagged: 0,
+            closed: subscribers.closed,
+            waker: None,
+        }));
+        subscribers.queues.push(Arc::downgrade(&queue));
+        EventStream(queue)
+    }
+
+    /// Returns the number of subscribers the event was delivered to.
+    pub(crate) fn publish(&self, event: E) -> usize {
+        let mut subscribers = lock(&self.subscribers);
+        // dropped subscribers are removed here
+        subscribers.queues.retain(|queue| {
+            let Some(queue) = queue.upgrade() else {
+                return false;
+            };
+            let mut queue = lock(&queue);
+            queue.events.push_back(event.clone());
+            if queue.capacity.is_some_and(|capacity| queue.events.len() > capacity) {
+                queue.events.pop_front();
+                queue.lagged += 1;
+            }
+            if let Some(waker) = queue.waker.take() {
+                waker.wake();
+            }
+            true
+        });
+        subscribers.queues.len()
+    }
+}
+
+/// Lets the untyped channel state end the event streams once the receiver terminated.
+pub(crate) trait ErasedEventHub: Any + Send + Sync {
+    fn close(&self);
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<E> ErasedEventHub for EventHub<E>
+where
+    E: Send + 'static,
+{
+    fn close(&self) {
+        let mut subscribers = lock(&self.subscribers);
+        subscribers.closed = true;
+        for queue in std::mem::take(&mut subscribers.queues) {
+            if let Some(queue) = queue.upgrade() {
+                let mut queue = lock(&queue);
+                queue.closed = true;
+                if let Some(waker) = queue.waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl<E> Default for EventHub<E> {
+    fn default() -> Self {
+        Self {
+            subscribers: Mutex::new(Subscribers {
+                closed: false,
+                queues: vec![],
+            }),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::any::{type_name, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use futures_util::future::LocalBoxFuture;
+
+use crate::any::AnyMessage;
+use crate::context::Context;
+use crate::handle::{
+    HandleAsyncConcurrentWithContext, HandleAsyncWithContext, HandleSyncConcurrentWithContext,
+    HandleSyncWithContext,
+};
+use crate::message::{mode, Message};
+use crate::message_set::{
+    MessageSet, MessageSetContains, MessageSetItem, MessageSetReceiver, MessageSetReplayItem,
+    MessageSetSender, MessageVariantSet,
+};
+use crate::router::DuplicateRoute;
+use crate::sync_wrapper::SyncWrapper;
+
+type SyncClosure<S> = dyn Fn(&mut S, AnyMessage) -> AnyMessage + Send + Sync;
+
+type AsyncClosure<S> =
+    dyn for<'a> Fn(&'a mut S, AnyMessage) -> LocalBoxFuture<'a, AnyMessage> + Send + Sync;
+
+type AsyncConcurrentClosure<S> =
+    dyn for<'a> Fn(&'a S, AnyMessage) -> LocalBoxFuture<'a, AnyMessage> + Send + Sync;
+
+type SyncConcurrentClosure<S> = dyn Fn(&S, AnyMessage) -> AnyMessage + Send + Sync;
+
+/// The message set of a [`FnHandler`] around the state `S`.
+///
+/// It contains every [`Message`], which is sent to the category of its mode. A message the handler
+/// has no closure for is dropped together with its replay, so awaiting the replay panics and
+/// `ReplyFuture::try_replay` fails.
+pub struct FnMsgSet<S>(PhantomData<fn(S)>);
+
+/// A message of a [`FnMsgSet`] in the category of the mode `C`.
+pub struct FnMsg<C> {
+    message_type: TypeId,
+    msg: SyncWrapper<AnyMessage>,
+    _marker: PhantomData<fn(C)>,
+}
+
+impl<C> FnMsg<C> {
+    fn new<M: Message>(msg: M) -> Self {
+        Self {
+            message_type: TypeId::of::<M>(),
+            msg: SyncWrapper::new(Box::new(msg)),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S> MessageVariantSet for FnMsgSet<S>
+where
+    S: 'static,
+{
+    type AsyncVariant = FnMsg<mode::Async>;
+    type SyncVariant = FnMsg<mode::Sync>;
+    type AsyncConcurrentVariant = FnMsg<mode::AsyncConcurrent>;
+    type SyncConcurrentVariant = FnMsg<mode::SyncConcurrent>;
+}
+
+impl<S> MessageSet for FnMsgSet<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Handler = FnHandler<S>;
+    type Event = ();
+    type Async = ();
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+impl<S, M> MessageSetContains<M> for FnMsgSet<S>
+where
+    S: Send + Sync + 'static,
+    M: Message,
+{
+    type Replay = M::Reply;
+
+    fn into_item(msg: M) -> MessageSetItem<Self> {
+        let mode = TypeId::of::<M::Mode>();
+        if mode == TypeId::of::<mode::Async>() {
+            MessageSetItem::Async(FnMsg::new(msg))
+        } else if mode == TypeId::of::<mode::Sync>() {
+            MessageSetItem::Sync(FnMsg::new(msg))
+        } else if mode == TypeId::of::<mode::AsyncConcurrent>() {
+            MessageSetItem::AsyncConcurrent(FnMsg::new(msg))
+        } else {
+            MessageSetItem::SyncConcurrent(FnMsg::new(msg))
+        }
+    }
+
+    fn into_replay_item(replay: M::Reply) -> MessageSetReplayItem<Self> {
+        // the category of a replay is never looked at, only its type
+        MessageSetReplayItem::Sync(Box::new(replay) as AnyMessage)
+    }
+
+    fn from_replay_item(replay: MessageSetReplayItem<Self>) -> M::Reply {
+        let (MessageSetReplayItem::Async(replay)
+        | MessageSetReplayItem::Sync(replay)
+        | MessageSetReplayItem::AsyncConcurrent(replay)
+        | MessageSetReplayItem::SyncConcurrent(replay)) = replay;
+        *replay
+            .downcast()
+            .expect("the closure of a message replays its `Message::Reply`")
+    }
+}
+
+/// A handler assembled from closures around `state` by [`FnHandlerBuilder`].
+pub struct FnHandler<S> {
+    state: S,
+    sync: HashMap<TypeId, Box<SyncClosure<S>>>,
+    async_: HashMap<TypeId, Box<AsyncClosure<S>>>,
+    async_concurrent: HashMap<TypeId, Box<AsyncConcurrentClosure<S>>>,
+    sync_concurrent: HashMap<TypeId, Box<SyncConcurrentClosure<S>>>,
+}
+
+impl<S> FnHandler<S> {
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+}
+
+impl<S> HandleSyncWithContext<FnMsgSet<S>, FnMsg<mode::Sync>> for FnHandler<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Replay = AnyMessage;
+
+    fn handle(
+        &mut self,
+        msg: FnMsg<mode::Sync>,
+        _cx: &mut Context<FnMsgSet<S>, AnyMessage>,
+    ) -> Option<Self::Replay> {
+        let f = self.sync.get(&msg.message_type)?;
+        Some(f(&mut self.state, msg.msg.into_inner()))
+    }
+}
+
+impl<S> HandleAsyncWithContext<FnMsgSet<S>, FnMsg<mode::Async>> for FnHandler<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Replay = AnyMessage;
+
+    async fn handle(
+        &mut self,
+        msg: FnMsg<mode::Async>,
+        _cx: &mut Context<FnMsgSet<S>, AnyMessage>,
+    ) -> Option<Self::Replay> {
+        let f = self.async_.get(&msg.message_type)?;
+        Some(f(&mut self.state, msg.msg.into_inner()).await)
+    }
+}
+
+impl<S> HandleAsyncConcurrentWithContext<FnMsgSet<S>, FnMsg<mode::AsyncConcurrent>> for FnHandler<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Replay = AnyMessage;
+
+    async fn handle(
+        &self,
+        msg: FnMsg<mode::AsyncConcurrent>,
+        _cx: &mut Context<FnMsgSet<S>, AnyMessage>,
+    ) -> Option<Self::Replay> {
+        let f = self.async_concurrent.get(&msg.message_type)?;
+        Some(f(&self.state, msg.msg.into_inner()).await)
+    }
+}
+
+impl<S> HandleSyncConcurrentWithContext<FnMsgSet<S>, FnMsg<mode::SyncConcurrent>> for FnHandler<S>
+where
+    S: Send + Sync + 'static,
+{
+    type Replay = AnyMessage;
+
+    fn handle(
+        &self,
+        msg: FnMsg<mode::SyncConcurrent>,
+        _cx: &mut Context<FnMsgSet<S>, AnyMessage>,
+    ) -> Option<Self::Replay> {
+        let f = self.sync_concurrent.get(&msg.message_type)?;
+        Some(f(&self.state, msg.msg.into_inner()))
+    }
+}
+
+/// Assembles a handler from closures around `state`, without a handler type or `#[msg_set]`.
+///
+/// Each closure handles a [`Message`] of the mode it is registered for and replays its `Reply`.
+/// The handler handles a [`FnMsgSet`], so its senders are typed like those of any set. Sync
+/// concurrent closures run on the blocking thread pool, like `HandleSyncConcurrent` handlers.
+///
+/// # Panics
+///
+/// The set contains every message, so sending one without a closure compiles. Its replay is
+/// dropped, so awaiting it panics with "the replay was dropped without being sent", use
+/// `ReplyFuture::try_replay` when that can happen.
+pub struct FnHandlerBuilder<S> {
+    handler: FnHandler<S>,
+    /// The first message type registered twice, reported by `build`.
+    duplicate: Option<&'static str>,
+}
+
+impl<S> FnHandlerBuilder<S>
+where
+    S: Send + Sync + 'static,
+{
+    pub fn new(state: S) -> Self {
+        Self {
+            handler: FnHandler {
+                state,
+                sync: HashMap::new(),
+                async_: HashMap::new(),
+                async_concurrent: HashMap::new(),
+                sync_concurrent: HashMap::new(),
+            },
+            duplicate: None,
+        }
+    }
+
+    /// Handles `M` with `f`. A message can only be registered once, `build` fails otherwise.
+    pub fn on_sync<M>(mut self, f: impl Fn(&mut S, M) -> M::Reply + Send + Sync + 'static) -> Self
+    where
+        M: Message<Mode = mode::Sync>,
+    {
+        register::<M, _>(
+            &mut self.handler.sync,
+            &mut self.duplicate,
+            Box::new(move |state, msg| Box::new(f(state, downcast_msg(msg)))),
+        );
+        self
+    }
+
+    pub fn on_async<M>(
+        mut self,
+        f: impl AsyncFn(&mut S, M) -> M::Reply + Send + Sync + 'static,
+    ) -> Self
+    where
+        M: Message<Mode = mode::Async>,
+    {
+        let f = Arc::new(f);
+        register::<M, _>(
+            &mut self.handler.async_,
+            &mut self.duplicate,
+            Box::new(move |state, msg| {
+                let f = f.clone();
+                Box::pin(async move {
+                    let replay: AnyMessage = Box::new(f(state, downcast_msg(msg)).await);
+                    replay
+                })
+            }),
+        );
+        self
+    }
+
+    pub fn on_async_concurrent<M>(
+        mut self,
+        f: impl AsyncFn(&S, M) -> M::Reply + Send + Sync + 'static,
+    ) -> Self
+    where
+        M: Message<Mode = mode::AsyncConcurrent>,
+    {
+        let f = Arc::new(f);
+        register::<M, _>(
+            &mut self.handler.async_concurrent,
+            &mut self.duplicate,
+            Box::new(move |state, msg| {
+                let f = f.clone();
+                Box::pin(async move {
+                    let replay: AnyMessage = Box::new(f(state, downcast_msg(msg)).await);
+                    replay
+                })
+            }),
+        );
+        self
+    }
+
+    pub fn on_sync_concurrent<M>(
+        mut self,
+        f: impl Fn(&S, M) -> M::Reply + Send + Sync + 'static,
+    ) -> Self
+    where
+        M: Message<Mode = mode::SyncConcurrent>,
+    {
+        register::<M, _>(
+            &mut self.handler.sync_concurrent,
+            &mut self.duplicate,
+            Box::new(move |state, msg| Box::new(f(state, downcast_msg(msg)))),
+        );
+        self
+    }
+
+    pub fn build(self) -> Result<FnHandler<S>, DuplicateRoute> {
+        if let Some(message) = self.duplicate {
+            return Err(DuplicateRoute { message });
+        }
+        Ok(self.handler)
+    }
+
+    /// Builds the handler together with a `msg_channel` of its set.
+    #[allow(clippy::type_complexity)]
+    pub fn build_channel(
+        self,
+    ) -> Result<
+        (
+            FnHandler<S>,
+            MessageSetSender<FnMsgSet<S>>,
+            MessageSetReceiver<FnMsgSet<S>>,
+        ),
+        DuplicateRoute,
+    > {
+        let handler = self.build()?;
+        let (sender, receiver) = crate::msg_channel();
+        Ok((handler, sender, receiver))
+    }
+}
+
+// keeps the first closure of a message, like `RouterBuilder`
+fn register<M: 'static, F>(
+    closures: &mut HashMap<TypeId, F>,
+    duplicate: &mut Option<&'static str>,
+    f: F,
+) {
+    match closures.entry(TypeId::of::<M>()) {
+        Entry::Vacant(entry) => {
+            entry.insert(f);
+        }
+        Entry::Occupied(_) => {
+            duplicate.get_or_insert(type_name::<M>());
+        }
+    }
+}
+
+fn downcast_msg<M: 'static>(msg: AnyMessage) -> M {
+    *msg.downcast()
+        .expect("closures are keyed by the type of their message")
+}
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use futures_util::stream::FuturesUnordered;
+use futures_util::{FutureExt, StreamExt};
+use thiserror::Error;
+use tokio::time::{timeout_at, Instant};
+
+use crate::lifecycle::ChannelId;
+use crate::message_set::{MessageSetContains, MessageSetSender};
+use crate::recipient::Recipient;
+use crate::reply::ReplyDropped;
+
+/// Senders of possibly different message sets that all handle `M` with the replay `R`.
+pub struct Group<M, R> {
+    members: Vec<Recipient<M, R>>,
+}
+
+impl<M, R> Clone for Group<M, R> {
+    fn clone(&self) -> Self {
+        Self {
+            members: self.members.clone(),
+        }
+    }
+}
+
+impl<M, R> Default for Group<M, R> {
+    fn default() -> Self {
+        Self { members: vec![] }
+    }
+}
+
+impl<M, R> Group<M, R>
+where
+    M: 'static,
+    R: Send + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<MS>(&mut self, sender: &MessageSetSender<MS>)
+    where
+        MS: MessageSetContains<M, Replay = R>,
+    {
+        self.add_recipient(sender.recipient());
+    }
+
+    pub fn add_recipient(&mut self, recipient: Recipient<M, R>) {
+        self.members.push(recipient);
+    }
+
+    pub fn remove(&mut self, id: ChannelId) {
+        self.members.retain(|member| member.id() != id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Sends a clone of `msg` to every member.
+    pub fn broadcast(&self, msg: M) -> Broadcast<R>
+    where
+        M: Clone,
+    {
+        let replays = FuturesUnordered::new();
+        let mut results: Vec<_> = self.members.iter().map(|_| None).collect();
+        for (index, member) in self.members.iter().enumerate() {
+            match member.send(msg.clone()) {
+                Ok(replay) => replays.push(
+                    async move { (index, replay.try_replay().await) }.boxed(),
+                ),
+                Err(_) => results[index] = Some(Err(BroadcastError::Unavailable)),
+            }
+        }
+        Broadcast {
+            replays,
+            results,
+            deadline: None,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BroadcastError {
+    /// The member stopped before it replied.
+    #[error("Unavailable")]
+    Unavailable,
+    #[error("TimedOut")]
+    TimedOut,
+    #[error("QuorumNotReached {replays}/{quorum}")]
+    QuorumNotReached { replays: usize, quorum: usize },
+}
+
+/// The pending replays of `Group::broadcast`.
+pub struct Broadcast<R> {
+    replays: FuturesUnordered<BoxFuture<'static, (usize, Result<R, ReplyDropped>)>>,
+    results: Vec<Option<Result<R, BroadcastError>>>,
+    deadline: Option<Instant>,
+}
+
+impl<R> Broadcast<R> {
+    /// Stops waiting for replays after `timeout`, the missing ones count as timed out.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    /// Waits for the next replay, `None` once every member replied or the deadline passed.
+    async fn next(&mut self) -> Option<(usize, Result<R, BroadcastError>)> {
+        let next = self.replays.next();
+        let next = match self.deadline {
+            Some(deadline) => timeout_at(deadline, next).await.ok()?,
+            None => next.await,
+        };
+        next.map(|(index, replay)| (index, replay.map_err(|_| BroadcastError::Unavailable)))
+    }
+
+    /// The result of every member, in the order they were added to the group.
+    pub async fn all(mut self) -> Vec<Result<R, BroadcastError>> {
+        while let Some((index, replay)) = self.next().await {
+            self.results[index] = Some(replay);
+        }
+        self.results
+            .into_iter()
+            .map(|result| result.unwrap_or(Err(BroadcastError::TimedOut)))
+            .collect()
+    }
+
+    /// The first `n` replays in the order they arrived, fewer if not enough members replied.
+    pub async fn first(mut self, n: usize) -> Vec<R> {
+        let mut replays = vec![];
+        while replays.len() < n {
+            match self.next().await {
+                Some((_, Ok(replay))) => replays.push(replay),
+                Some((_, Err(_))) => continue,
+                None => break,
+            }
+        }
+        replays
+    }
+
+    /// The first `quorum` replays, fails once they can no longer be reached.
+    pub async fn quorum(mut self, quorum: usize) -> Result<Vec<R>, BroadcastError> {
+        let mut replays = vec![];
+        let mut pending = self.replays.len();
+        while replays.len() < quorum {
+            if replays.len() + pending < quorum {
+                return Err(BroadcastError::QuorumNotReached {
+                    replays: replays.len(),
+                    quorum,
+                });
+            }
+            match self.next().await {
+                Some((_, Ok(replay))) => replays.push(replay),
+                Some((_, Err(_))) => {}
+                None => return Err(BroadcastError::TimedOut),
+            }
+            pending -= 1;
+        }
+        Ok(replays)
+    }
+
+    /// `quorum` with a majority of the members.
+    pub async fn majority(self) -> Result<Vec<R>, BroadcastError> {
+        let quorum = self.results.len() / 2 + 1;
+        self.quorum(quorum).await
+    }
+}
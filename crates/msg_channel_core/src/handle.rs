@@ -1,9 +1,9 @@
 use std::future::Future;
 
-pub trait HandleReplay<M> {
-    type Replay: Send + 'static;
-    type MsgReplay: Into<Self::Replay>;
-}
+use futures_util::future::LocalBoxFuture;
+
+use crate::context::Context;
+use crate::message_set::MessageSet;
 
 pub trait HandleSync<M>: Sync {
     fn is_blocking(&self, _msg: &M) -> bool {
@@ -50,3 +50,152 @@ impl<T: Sync> HandleSyncConcurrent<()> for T {
 
     fn handle(&self, _: ()) -> Self::Replay {}
 }
+
+// The `*WithContext` handlers get the `Context` of the set they are dispatched in, and return
+// `None` when they took the sender of the reply out of it. Every plain handler is one as well.
+
+pub trait HandleSyncWithContext<MS, M>: Sync
+where
+    MS: MessageSet,
+{
+    fn is_blocking(&self, _msg: &M) -> bool {
+        true
+    }
+    type Replay: Send + 'static;
+    fn handle(&mut self, msg: M, cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay>;
+}
+
+impl<MS, M, T> HandleSyncWithContext<MS, M> for T
+where
+    MS: MessageSet,
+    T: HandleSync<M>,
+{
+    fn is_blocking(&self, msg: &M) -> bool {
+        HandleSync::is_blocking(self, msg)
+    }
+    type Replay = T::Replay;
+
+    fn handle(&mut self, msg: M, _cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay> {
+        Some(HandleSync::handle(self, msg))
+    }
+}
+
+pub trait HandleAsyncWithContext<MS, M>
+where
+    MS: MessageSet,
+{
+    type Replay: Send + 'static;
+    fn handle(
+        &mut self,
+        msg: M,
+        cx: &mut Context<MS, Self::Replay>,
+    ) -> impl Future<Output = Option<Self::Replay>>;
+}
+
+impl<MS, M, T> HandleAsyncWithContext<MS, M> for T
+where
+    MS: MessageSet,
+    T: HandleAsync<M>,
+{
+    type Replay = T::Replay;
+
+    async fn handle(
+        &mut self,
+        msg: M,
+        _cx: &mut Context<MS, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        Some(HandleAsync::handle(self, msg).await)
+    }
+}
+
+pub trait HandleAsyncConcurrentWithContext<MS, M>
+where
+    MS: MessageSet,
+{
+    type Replay: Send + 'static;
+    fn handle(
+        &self,
+        msg: M,
+        cx: &mut Context<MS, Self::Replay>,
+    ) -> impl Future<Output = Option<Self::Replay>>;
+}
+
+impl<MS, M, T> HandleAsyncConcurrentWithContext<MS, M> for T
+where
+    MS: MessageSet,
+    T: HandleAsyncConcurrent<M>,
+{
+    type Replay = T::Replay;
+
+    async fn handle(
+        &self,
+        msg: M,
+        _cx: &mut Context<MS, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        Some(HandleAsyncConcurrent::handle(self, msg).await)
+    }
+}
+
+pub trait HandleSyncConcurrentWithContext<MS, M>: Sync
+where
+    MS: MessageSet,
+{
+    fn is_blocking(&self, _msg: &M) -> bool {
+        true
+    }
+    type Replay: Send + 'static;
+    fn handle(&self, msg: M, cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay>;
+}
+
+impl<MS, M, T> HandleSyncConcurrentWithContext<MS, M> for T
+where
+    MS: MessageSet,
+    T: HandleSyncConcurrent<M>,
+{
+    fn is_blocking(&self, msg: &M) -> bool {
+        HandleSyncConcurrent::is_blocking(self, msg)
+    }
+    type Replay = T::Replay;
+
+    fn handle(&self, msg: M, _cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay> {
+        Some(HandleSyncConcurrent::handle(self, msg))
+    }
+}
+
+// `HandleAsync` and `HandleAsyncConcurrent` return `impl Future`, so they can't be trait objects.
+// The `*Boxed` versions box the future and are implemented by every handler of those. The boxed
+// future is not `Send`, since that can't be known for a generic handler.
+
+pub trait HandleAsyncBoxed<M> {
+    type Replay: Send + 'static;
+    fn handle_boxed(&mut self, msg: M) -> LocalBoxFuture<'_, Self::Replay>;
+}
+
+impl<M, T> HandleAsyncBoxed<M> for T
+where
+    M: 'static,
+    T: HandleAsync<M>,
+{
+    type Replay = T::Replay;
+
+    fn handle_boxed(&mut self, msg: M) -> LocalBoxFuture<'_, Self::Replay> {
+        Box::pin(HandleAsync::handle(self, msg))
+    }
+}
+
+pub trait HandleAsyncConcurrentBoxed<M> {
+    type Replay: Send + 'static;
+    fn handle_boxed(&self, msg: M) -> LocalBoxFuture<'_, Self::Replay>;
+}
+
+impl<M, T> HandleAsyncConcurrentBoxed<M> for T
+where
+    M: 'static,
+    T: HandleAsyncConcurrent<M>,
+{
+    type Replay = T::Replay;
+
+    fn handle_boxed(&self, msg: M) -> LocalBoxFuture<'_, Self::Replay> {
+        Box::pin(HandleAsyncConcurrent::handle(self, msg))
+    }
+}
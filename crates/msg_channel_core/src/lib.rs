@@ -1,21 +1,49 @@
+use std::sync::Arc;
+
+use crate::lifecycle::ChannelState;
 use crate::message_set::{MessageSet, MessageSetReceiver, MessageSetSender};
 
+pub mod any;
+pub mod bus;
+pub mod context;
+pub mod event;
+pub mod fn_handler;
+pub mod group;
 pub mod handle;
-pub mod macros;
+pub mod lifecycle;
+pub mod message;
 pub mod message_set;
+pub mod recipient;
+pub mod reply;
+pub mod request;
+pub mod session;
+pub mod router;
+pub mod supervisor;
+mod sync_wrapper;
+pub mod timer;
 
 pub fn msg_channel<MS>() -> (MessageSetSender<MS>, MessageSetReceiver<MS>)
 where
     MS: MessageSet,
 {
     let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    let address = sender.downgrade();
+    let state = Arc::new(ChannelState::new());
 
     (
-        MessageSetSender { sender },
+        MessageSetSender {
+            sender,
+            state: state.clone(),
+        },
         MessageSetReceiver {
             receiver,
             msg_queue: vec![],
             concurrent_msg_buf: Default::default(),
+            address,
+            state,
+            termination: None,
+            stopped: false,
+            keep_alive: None,
         },
     )
 }
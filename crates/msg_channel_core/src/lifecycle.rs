@@ -0,0 +1,123 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+
+use crate::event::{ErasedEventHub, EventHub};
+use crate::message::{mode, Message};
+use crate::timer::TimerWheel;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChannelId(u64);
+
+impl ChannelId {
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        ChannelId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// Every sender was dropped and all queued messages were handled.
+    Closed,
+    /// The receiver was stopped by `MessageSetReceiver::stop` and drained.
+    Stopped,
+    Panicked,
+    Failed(String),
+    /// The receiver was dropped while it could still receive messages.
+    Dropped,
+}
+
+/// Delivered to the watchers of a message set receiver once it stops.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Terminated {
+    pub id: ChannelId,
+    pub reason: TerminationReason,
+}
+
+impl Message for Terminated {
+    type Reply = ();
+    type Mode = mode::Sync;
+}
+
+type TerminateHook = Box<dyn FnOnce(&Terminated) + Send>;
+
+#[derive(Default)]
+struct Lifecycle {
+    terminated: Option<Terminated>,
+    hooks: Vec<TerminateHook>,
+}
+
+pub(crate) struct ChannelState {
+    pub(crate) id: ChannelId,
+    lifecycle: Mutex<Lifecycle>,
+    timers: OnceLock<TimerWheel>,
+    events: OnceLock<Box<dyn ErasedEventHub>>,
+    pub(crate) stop_requested: AtomicBool,
+}
+
+impl ChannelState {
+    pub(crate) fn new() -> Self {
+        Self {
+            id: ChannelId::next(),
+            lifecycle: Default::default(),
+            timers: OnceLock::new(),
+            events: OnceLock::new(),
+            stop_requested: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn timers(&self) -> &TimerWheel {
+        self.timers.get_or_init(TimerWheel::start)
+    }
+
+    /// The events of the set, the channel state of a set always has the same event type.
+    pub(crate) fn events<E>(&self) -> &EventHub<E>
+    where
+        E: Send + 'static,
+    {
+        self.events
+            .get_or_init(|| Box::new(EventHub::<E>::default()))
+            .as_any()
+            .downcast_ref()
+            .expect("the event type of a channel never changes")
+    }
+
+    // hooks never run while the lock is held, but `terminate` may run during a panic
+    fn lifecycle(&self) -> MutexGuard<'_, Lifecycle> {
+        self.lifecycle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn on_terminate(&self, hook: TerminateHook) {
+        let mut lifecycle = self.lifecycle();
+        match lifecycle.terminated.clone() {
+            None => lifecycle.hooks.push(hook),
+            Some(terminated) => {
+                drop(lifecycle);
+                hook(&terminated);
+            }
+        }
+    }
+
+    pub(crate) fn terminate(&self, reason: TerminationReason) {
+        let terminated = Terminated {
+            id: self.id,
+            reason,
+        };
+        let hooks = {
+            let mut lifecycle = self.lifecycle();
+            if lifecycle.terminated.is_some() {
+                return;
+            }
+            lifecycle.terminated = Some(terminated.clone());
+            std::mem::take(&mut lifecycle.hooks)
+        };
+        for hook in hooks {
+            hook(&terminated);
+        }
+        if let Some(events) = self.events.get() {
+            events.close();
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use std::cmp::Ordering;
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+use crate::context::Context;
+use crate::handle::{
+    HandleAsyncConcurrentWithContext, HandleAsyncWithContext, HandleSyncConcurrentWithContext,
+    HandleSyncWithContext,
+};
+use crate::message_set::MessageSet;
+
+/// Declares the replay and the dispatch mode of a message, usually with `#[derive(Message)]`.
+///
+/// Sets listing their messages in `type Messages = (...)` sort them into categories by their
+/// mode. The handler of a message must replay `Reply`.
+pub trait Message: Send + 'static {
+    type Reply: Send + 'static;
+    type Mode: MessageMode;
+}
+
+/// The dispatch modes of [`Message`], named like the categories of `MessageSet`.
+pub mod mode {
+    pub struct Async;
+    pub struct Sync;
+    pub struct AsyncConcurrent;
+    pub struct SyncConcurrent;
+}
+
+/// Implemented by the types of [`mode`].
+pub trait MessageMode:
+    Select<mode::Async>
+    + Select<mode::Sync>
+    + Select<mode::AsyncConcurrent>
+    + Select<mode::SyncConcurrent>
+    + 'static
+{
+}
+
+impl MessageMode for mode::Async {}
+impl MessageMode for mode::Sync {}
+impl MessageMode for mode::AsyncConcurrent {}
+impl MessageMode for mode::SyncConcurrent {}
+
+// The variant enums of a set with `type Messages` list every message in every category. The slot
+// of a message holds it in the category of its mode, and can't be constructed in the others.
+
+/// Selects the slot of a message in the category `C`.
+pub trait Select<C> {
+    type Slot<M: Message>: ModeSlot<Msg = M>;
+}
+
+macro_rules! select {
+    ($mode:ident: $($category:ident => $slot:ident),*) => {
+        $(
+            impl Select<mode::$category> for mode::$mode {
+                type Slot<M: Message> = $slot<M>;
+            }
+        )*
+    };
+}
+
+select!(Async: Async => Active, Sync => Inactive, AsyncConcurrent => Inactive, SyncConcurrent => Inactive);
+select!(Sync: Async => Inactive, Sync => Active, AsyncConcurrent => Inactive, SyncConcurrent => Inactive);
+select!(AsyncConcurrent: Async => Inactive, Sync => Inactive, AsyncConcurrent => Active, SyncConcurrent => Inactive);
+select!(SyncConcurrent: Async => Inactive, Sync => Inactive, AsyncConcurrent => Inactive, SyncConcurrent => Active);
+
+pub type SlotOf<M, C> = <<M as Message>::Mode as Select<C>>::Slot<M>;
+
+/// The slot of a message in the category of its mode.
+///
+/// It's transparent to the derives of `#[msg_set(derive(...))]`, e.g. it's debugged as `M`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Active<M>(pub M);
+
+impl<M> fmt::Debug for Active<M>
+where
+    M: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The slot of a message in the other categories, and of the replays it can't have there.
+///
+/// It can't be constructed, so it implements the derives of `#[msg_set(derive(...))]` for any `M`.
+pub struct Inactive<M>(Infallible, PhantomData<fn() -> M>);
+
+impl<M> Inactive<M> {
+    pub fn unreachable(&self) -> ! {
+        match self.0 {}
+    }
+}
+
+impl<M> fmt::Debug for Inactive<M> {
+    fn fmt(&self, _f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.unreachable()
+    }
+}
+
+impl<M> Clone for Inactive<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for Inactive<M> {}
+
+impl<M> PartialEq for Inactive<M> {
+    fn eq(&self, _other: &Self) -> bool {
+        self.unreachable()
+    }
+}
+
+impl<M> Eq for Inactive<M> {}
+
+impl<M> PartialOrd for Inactive<M> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<M> Ord for Inactive<M> {
+    fn cmp(&self, _other: &Self) -> Ordering {
+        self.unreachable()
+    }
+}
+
+impl<M> Hash for Inactive<M> {
+    fn hash<S: Hasher>(&self, _state: &mut S) {
+        self.unreachable()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<M> serde::Serialize for Inactive<M> {
+    fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        self.unreachable()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M> serde::Deserialize<'de> for Inactive<M> {
+    fn deserialize<D: serde::Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
+        Err(serde::de::Error::custom(
+            "the message isn't in the category of its mode",
+        ))
+    }
+}
+
+pub trait ModeSlot: Send + Sized + 'static {
+    type Msg: Message;
+    type Replay: Send + 'static;
+
+    fn try_from_msg(msg: Self::Msg) -> Result<Self, Self::Msg>;
+
+    fn try_from_reply(
+        reply: <Self::Msg as Message>::Reply,
+    ) -> Result<Self::Replay, <Self::Msg as Message>::Reply>;
+
+    fn into_reply(replay: Self::Replay) -> <Self::Msg as Message>::Reply;
+}
+
+impl<M> ModeSlot for Active<M>
+where
+    M: Message,
+{
+    type Msg = M;
+    type Replay = M::Reply;
+
+    fn try_from_msg(msg: M) -> Result<Self, M> {
+        Ok(Active(msg))
+    }
+
+    fn try_from_reply(reply: M::Reply) -> Result<M::Reply, M::Reply> {
+        Ok(reply)
+    }
+
+    fn into_reply(replay: M::Reply) -> M::Reply {
+        replay
+    }
+}
+
+impl<M> ModeSlot for Inactive<M>
+where
+    M: Message,
+{
+    type Msg = M;
+    type Replay = Self;
+
+    fn try_from_msg(msg: M) -> Result<Self, M> {
+        Err(msg)
+    }
+
+    fn try_from_reply(reply: M::Reply) -> Result<Self, M::Reply> {
+        Err(reply)
+    }
+
+    fn into_reply(replay: Self) -> M::Reply {
+        replay.unreachable()
+    }
+}
+
+pub trait AsyncSlot<MS, H>: ModeSlot
+where
+    MS: MessageSet,
+{
+    fn handle(
+        self,
+        handler: &mut H,
+        cx: &mut Context<MS, Self::Replay>,
+    ) -> impl Future<Output = Option<Self::Replay>>;
+}
+
+impl<MS, H, M> AsyncSlot<MS, H> for Active<M>
+where
+    MS: MessageSet,
+    M: Message,
+    H: HandleAsyncWithContext<MS, M, Replay = M::Reply>,
+{
+    fn handle(
+        self,
+        handler: &mut H,
+        cx: &mut Context<MS, Self::Replay>,
+    ) -> impl Future<Output = Option<Self::Replay>> {
+        HandleAsyncWithContext::handle(handler, self.0, cx)
+    }
+}
+
+impl<MS, H, M> AsyncSlot<MS, H> for Inactive<M>
+where
+    MS: MessageSet,
+    M: Message,
+{
+    async fn handle(
+        self,
+        _handler: &mut H,
+        _cx: &mut Context<MS, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        match self.0 {}
+    }
+}
+
+pub trait SyncSlot<MS, H>: ModeSlot
+where
+    MS: MessageSet,
+{
+    fn is_blocking(&self, handler: &H) -> bool;
+
+    fn handle(self, handler: &mut H, cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay>;
+}
+
+impl<MS, H, M> SyncSlot<MS, H> for Active<M>
+where
+    MS: MessageSet,
+    M: Message,
+    H: HandleSyncWithContext<MS, M, Replay = M::Reply>,
+{
+    fn is_blocking(&self, handler: &H) -> bool {
+        HandleSyncWithContext::is_blocking(handler, &self.0)
+    }
+
+    fn handle(self, handler: &mut H, cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay> {
+        HandleSyncWithContext::handle(handler, self.0, cx)
+    }
+}
+
+impl<MS, H, M> SyncSlot<MS, H> for Inactive<M>
+where
+    MS: MessageSet,
+    M: Message,
+{
+    fn is_blocking(&self, _handler: &H) -> bool {
+        match self.0 {}
+    }
+
+    fn handle(self, _handler: &mut H, _cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay> {
+        match self.0 {}
+    }
+}
+
+pub trait AsyncConcurrentSlot<MS, H>: ModeSlot
+where
+    MS: MessageSet,
+{
+    fn handle(
+        self,
+        handler: &H,
+        cx: &mut Context<MS, Self::Replay>,
+    ) -> impl Future<Output = Option<Self::Replay>>;
+}
+
+impl<MS, H, M> AsyncConcurrentSlot<MS, H> for Active<M>
+where
+    MS: MessageSet,
+    M: Message,
+    H: HandleAsyncConcurrentWithContext<MS, M, Replay = M::Reply>,
+{
+    fn handle(
+        self,
+        handler: &H,
+        cx: &mut Context<MS, Self::Replay>,
+    ) -> impl Future<Output = Option<Self::Replay>> {
+        HandleAsyncConcurrentWithContext::handle(handler, self.0, cx)
+    }
+}
+
+impl<MS, H, M> AsyncConcurrentSlot<MS, H> for Inactive<M>
+where
+    MS: MessageSet,
+    M: Message,
+{
+    async fn handle(
+        self,
+        _handler: &H,
+        _cx: &mut Context<MS, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        match self.0 {}
+    }
+}
+
+pub trait SyncConcurrentSlot<MS, H>: ModeSlot
+where
+    MS: MessageSet,
+{
+    fn is_blocking(&self, handler: &H) -> bool;
+
+    fn handle(self, handler: &H, cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay>;
+}
+
+impl<MS, H, M> SyncConcurrentSlot<MS, H> for Active<M>
+where
+    MS: MessageSet,
+    M: Message,
+    H: HandleSyncConcurrentWithContext<MS, M, Replay = M::Reply>,
+{
+    fn is_blocking(&self, handler: &H) -> bool {
+        HandleSyncConcurrentWithContext::is_blocking(handler, &self.0)
+    }
+
+    fn handle(self, handler: &H, cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay> {
+        HandleSyncConcurrentWithContext::handle(handler, self.0, cx)
+    }
+}
+
+impl<MS, H, M> SyncConcurrentSlot<MS, H> for Inactive<M>
+where
+    MS: MessageSet,
+    M: Message,
+{
+    fn is_blocking(&self, _handler: &H) -> bool {
+        match self.0 {}
+    }
+
+    fn handle(self, _handler: &H, _cx: &mut Context<MS, Self::Replay>) -> Option<Self::Replay> {
+        match self.0 {}
+    }
+}
@@ -1,16 +1,30 @@
-use std::future::{Future, ready};
+use std::fmt;
+use std::future::ready;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
 
 use futures_util::{FutureExt, StreamExt};
 use futures_util::future::Either;
 use futures_util::stream::FuturesUnordered;
 use thiserror::Error;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TryRecvError;
 use tokio::task::{JoinError, JoinHandle};
+use tokio::time::Instant;
 
+use crate::context::Context;
 use crate::handle::{
-    HandleAsync, HandleAsyncConcurrent, HandleReplay, HandleSync, HandleSyncConcurrent,
+    HandleAsyncConcurrentWithContext, HandleAsyncWithContext, HandleSyncConcurrentWithContext,
+    HandleSyncWithContext,
 };
+use crate::event::{EventPolicy, EventStream};
+use crate::lifecycle::{ChannelId, ChannelState, Terminated, TerminationReason};
+use crate::recipient::Recipient;
+use crate::reply::{reply_channel, ReplyFuture, ReplyTo};
+use crate::request::{RequestSink, RequestStream};
+use crate::session::Session;
+use crate::timer::{deadline_after, TimerHandle};
 
 pub enum MessageSetItem<MS>
 where
@@ -25,12 +39,103 @@ pub enum MessageSetReplayItem<MS>
 where
     MS: MessageSet,
 {
-    Async(<MS::Handler as HandleAsync<MS::AsyncVariant>>::Replay),
-    Sync(<MS::Handler as HandleSync<MS::SyncVariant>>::Replay),
-    AsyncConcurrent(<MS::Handler as HandleAsyncConcurrent<MS::AsyncConcurrentVariant>>::Replay),
-    SyncConcurrent(<MS::Handler as HandleSyncConcurrent<MS::SyncConcurrentVariant>>::Replay),
+    Async(<MS::Handler as HandleAsyncWithContext<MS, MS::AsyncVariant>>::Replay),
+    Sync(<MS::Handler as HandleSyncWithContext<MS, MS::SyncVariant>>::Replay),
+    AsyncConcurrent(
+        <MS::Handler as HandleAsyncConcurrentWithContext<MS, MS::AsyncConcurrentVariant>>::Replay,
+    ),
+    SyncConcurrent(
+        <MS::Handler as HandleSyncConcurrentWithContext<MS, MS::SyncConcurrentVariant>>::Replay,
+    ),
 }
 
+// the traits the variant enums derive with `#[msg_set(derive(...), reply_derive(...))]`, for
+// logging in-flight items
+macro_rules! item_impls {
+    ($item:ident { $($category:ident($ty:ty) = $index:literal),* }) => {
+        impl<MS> fmt::Debug for $item<MS>
+        where
+            MS: MessageSet,
+            $($ty: fmt::Debug,)*
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $(Self::$category(variant) => {
+                        f.debug_tuple(stringify!($category)).field(variant).finish()
+                    })*
+                }
+            }
+        }
+
+        impl<MS> Clone for $item<MS>
+        where
+            MS: MessageSet,
+            $($ty: Clone,)*
+        {
+            fn clone(&self) -> Self {
+                match self {
+                    $(Self::$category(variant) => Self::$category(variant.clone()),)*
+                }
+            }
+        }
+
+        impl<MS> PartialEq for $item<MS>
+        where
+            MS: MessageSet,
+            $($ty: PartialEq,)*
+        {
+            fn eq(&self, other: &Self) -> bool {
+                match (self, other) {
+                    $((Self::$category(a), Self::$category(b)) => a == b,)*
+                    _ => false,
+                }
+            }
+        }
+
+        impl<MS> Eq for $item<MS>
+        where
+            MS: MessageSet,
+            $($ty: Eq,)*
+        {
+        }
+
+        #[cfg(feature = "serde")]
+        impl<MS> serde::Serialize for $item<MS>
+        where
+            MS: MessageSet,
+            $($ty: serde::Serialize,)*
+        {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                match self {
+                    $(Self::$category(variant) => serializer.serialize_newtype_variant(
+                        stringify!($item),
+                        $index,
+                        stringify!($category),
+                        variant,
+                    ),)*
+                }
+            }
+        }
+    };
+}
+
+item_impls!(MessageSetItem {
+    Async(MS::AsyncVariant) = 0,
+    Sync(MS::SyncVariant) = 1,
+    AsyncConcurrent(MS::AsyncConcurrentVariant) = 2,
+    SyncConcurrent(MS::SyncConcurrentVariant) = 3
+});
+item_impls!(MessageSetReplayItem {
+    Async(<MS::Handler as HandleAsyncWithContext<MS, MS::AsyncVariant>>::Replay) = 0,
+    Sync(<MS::Handler as HandleSyncWithContext<MS, MS::SyncVariant>>::Replay) = 1,
+    AsyncConcurrent(
+        <MS::Handler as HandleAsyncConcurrentWithContext<MS, MS::AsyncConcurrentVariant>>::Replay
+    ) = 2,
+    SyncConcurrent(
+        <MS::Handler as HandleSyncConcurrentWithContext<MS, MS::SyncConcurrentVariant>>::Replay
+    ) = 3
+});
+
 pub trait MessageVariantSet: 'static {
     type AsyncVariant: Send + 'static;
     type SyncVariant: Send + 'static;
@@ -38,56 +143,204 @@ pub trait MessageVariantSet: 'static {
     type SyncConcurrentVariant: Send + 'static;
 }
 
-pub trait MessageSet: MessageVariantSet
+pub trait MessageSet: MessageVariantSet + Sized
 where
-    Self::Handler: HandleAsync<Self::AsyncVariant>,
-    Self::Handler: HandleSync<Self::SyncVariant>,
-    Self::Handler: HandleAsyncConcurrent<Self::AsyncConcurrentVariant>,
-    Self::Handler: HandleSyncConcurrent<Self::SyncConcurrentVariant>,
+    Self::Handler: HandleAsyncWithContext<Self, Self::AsyncVariant>,
+    Self::Handler: HandleSyncWithContext<Self, Self::SyncVariant>,
+    Self::Handler: HandleAsyncConcurrentWithContext<Self, Self::AsyncConcurrentVariant>,
+    Self::Handler: HandleSyncConcurrentWithContext<Self, Self::SyncConcurrentVariant>,
 {
     type Handler: 'static;
+    /// Published by the handler through `Context::publish`, `()` when the set has no events.
+    type Event: Clone + Send + 'static;
     type Async;
     type Sync;
     type AsyncConcurrent;
     type SyncConcurrent;
 }
 
+pub trait MessageSetContains<M>: MessageSet {
+    /// The replay of the handler of `M`.
+    type Replay: Send + 'static;
+
+    fn into_item(msg: M) -> MessageSetItem<Self>;
+
+    fn into_replay_item(replay: Self::Replay) -> MessageSetReplayItem<Self>;
+
+    fn from_replay_item(replay: MessageSetReplayItem<Self>) -> Self::Replay;
+}
+
+pub type MsgAndReplaySender<MS> = (MessageSetItem<MS>, ReplyTo<MessageSetReplayItem<MS>>);
+
+pub type MsgSendError<MS> = mpsc::error::SendError<MsgAndReplaySender<MS>>;
+
 pub struct MessageSetSender<T>
 where
     T: MessageSet,
 {
-    pub sender:
-        mpsc::UnboundedSender<(MessageSetItem<T>, oneshot::Sender<MessageSetReplayItem<T>>)>,
+    pub sender: mpsc::UnboundedSender<MsgAndReplaySender<T>>,
+    pub(crate) state: Arc<ChannelState>,
+}
+
+impl<MS> Clone for MessageSetSender<MS>
+where
+    MS: MessageSet,
+{
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            state: self.state.clone(),
+        }
+    }
 }
 
 impl<MS> MessageSetSender<MS>
 where
     MS: MessageSet,
 {
-    pub fn send<M>(
+    pub fn send<M: 'static>(
         &self,
         msg: M,
-    ) -> Result<
-        impl Future<Output = <MS::Handler as HandleReplay<M>>::Replay>,
-        mpsc::error::SendError<(
-            MessageSetItem<MS>,
-            oneshot::Sender<MessageSetReplayItem<MS>>,
-        )>,
-    >
+    ) -> Result<ReplyFuture<MS::Replay>, MsgSendError<MS>>
     where
-        M: Into<MessageSetItem<MS>>,
-        MS::Handler: HandleReplay<M>,
-        <MS::Handler as HandleReplay<M>>::MsgReplay: From<MessageSetReplayItem<MS>>,
+        MS: MessageSetContains<M>,
     {
-        let msg_variant = msg.into();
-        let (replay_sender, replay_receiver) = oneshot::channel();
-        self.sender.send((msg_variant, replay_sender))?;
-        Ok(async move {
-            let replay = replay_receiver.await.unwrap();
-            let replay: <MS::Handler as HandleReplay<M>>::MsgReplay = replay.into();
-            replay.into()
+        let (reply_to, replay) = reply_channel();
+        self.forward(msg, reply_to)?;
+        Ok(replay)
+    }
+
+    /// Sends a message that carries a stream of chunks, built from the stream by `msg`.
+    ///
+    /// The chunks are pushed through the returned sink, and the replay comes once the handler
+    /// consumed them. At most `buffer` chunks are buffered, at least one.
+    #[allow(clippy::type_complexity)]
+    pub fn send_stream<M: 'static, T>(
+        &self,
+        buffer: usize,
+        msg: impl FnOnce(RequestStream<T>) -> M,
+    ) -> Result<(RequestSink<T>, ReplyFuture<MS::Replay>), MsgSendError<MS>>
+    where
+        MS: MessageSetContains<M>,
+    {
+        let (sink, stream) = RequestStream::channel(buffer);
+        let replay = self.send(msg(stream))?;
+        Ok((sink, replay))
+    }
+
+    /// Opens a [`Session`] with the handler of `M`, which gets the other end in the message
+    /// built by `msg`.
+    ///
+    /// `buffer` messages are buffered in each direction, at least one. The replay comes once the handler
+    /// handled the message, which may be before or after the session ends.
+    #[allow(clippy::type_complexity)]
+    pub fn open_session<M: 'static, In, Out>(
+        &self,
+        buffer: usize,
+        msg: impl FnOnce(Session<Out, In>) -> M,
+    ) -> Result<(Session<In, Out>, ReplyFuture<MS::Replay>), MsgSendError<MS>>
+    where
+        MS: MessageSetContains<M>,
+    {
+        let (session, handler_session) = Session::pair(buffer);
+        let replay = self.send(msg(handler_session))?;
+        Ok((session, replay))
+    }
+
+    /// Sends `msg` with the replay going to `reply_to` instead of back to this caller.
+    ///
+    /// A handler forwards a request to another set by passing on the slot it took with
+    /// `Context::take_reply`, the other handler then replies to the original caller directly.
+    pub fn forward<M: 'static>(
+        &self,
+        msg: M,
+        reply_to: ReplyTo<MS::Replay>,
+    ) -> Result<(), MsgSendError<MS>>
+    where
+        MS: MessageSetContains<M>,
+    {
+        self.sender
+            .send((MS::into_item(msg), reply_to.map(MS::from_replay_item)))
+    }
+
+    /// Sends `msg` after `delay`, ignoring the reply. Delays are at most about two years, longer
+    /// ones are shortened. Must be called inside a tokio runtime.
+    pub fn send_after<M>(&self, delay: Duration, msg: M) -> TimerHandle
+    where
+        MS: MessageSetContains<M>,
+    {
+        self.send_at(deadline_after(delay), msg)
+    }
+
+    /// Sends `msg` at `deadline`, ignoring the reply. Must be called inside a tokio runtime.
+    pub fn send_at<M>(&self, deadline: Instant, msg: M) -> TimerHandle
+    where
+        MS: MessageSetContains<M>,
+    {
+        let msg = MS::into_item(msg);
+        let sender = self.sender.clone();
+        self.state.timers().once(deadline, move || {
+            let _ = sender.send((msg, ReplyTo::discard()));
         })
     }
+
+    /// Sends a message created by `f` every `period`, starting one `period` from now, until the
+    /// timer is cancelled or the receiver stops. The timer keeps the channel open. Must be called
+    /// inside a tokio runtime.
+    pub fn send_interval<M, F>(&self, period: Duration, mut f: F) -> TimerHandle
+    where
+        MS: MessageSetContains<M>,
+        F: FnMut() -> M + Send + 'static,
+    {
+        let sender = self.sender.clone();
+        self.state.timers().interval(period, move || {
+            sender
+                .send((MS::into_item(f()), ReplyTo::discard()))
+                .is_ok()
+        })
+    }
+
+    pub fn id(&self) -> ChannelId {
+        self.state.id
+    }
+
+    /// Erases the set of this sender, keeping only the ability to send `M`.
+    pub fn recipient<M: 'static>(&self) -> Recipient<M, MS::Replay>
+    where
+        MS: MessageSetContains<M>,
+    {
+        self.clone().into()
+    }
+
+    /// Subscribes to the events published by the handler, with the default [`EventPolicy`].
+    pub fn subscribe(&self) -> EventStream<MS::Event> {
+        self.subscribe_with(EventPolicy::default())
+    }
+
+    pub fn subscribe_with(&self, policy: EventPolicy) -> EventStream<MS::Event> {
+        self.state.events().subscribe(policy)
+    }
+
+    /// Registers a hook that runs once the receiver of this channel stops.
+    pub fn on_terminate(&self, hook: impl FnOnce(&Terminated) + Send + 'static) {
+        self.state.on_terminate(Box::new(hook));
+    }
+
+    /// Delivers a [`Terminated`] message to this set once the receiver of `watched` stops.
+    ///
+    /// The watcher is only held weakly, so watching does not keep its channel open.
+    pub fn watch<W>(&self, watched: &MessageSetSender<W>)
+    where
+        W: MessageSet,
+        MS: MessageSetContains<Terminated>,
+    {
+        let watcher = self.sender.downgrade();
+        watched.on_terminate(move |terminated| {
+            if let Some(watcher) = watcher.upgrade() {
+                let _ = watcher.send((MS::into_item(terminated.clone()), ReplyTo::discard()));
+            }
+        });
+    }
 }
 
 
@@ -99,32 +352,64 @@ pub enum MsgSetRecvError {
     JoinError(JoinError)
 }
 
-type SyncConcurrentRelayAndSender<MS> = (
-    <<MS as MessageSet>::Handler as HandleSyncConcurrent<
-        <MS as MessageVariantSet>::SyncConcurrentVariant,
-    >>::Replay,
-    oneshot::Sender<MessageSetReplayItem<MS>>,
+type SyncConcurrentReplay<MS> = <<MS as MessageSet>::Handler as HandleSyncConcurrentWithContext<
+    MS,
+    <MS as MessageVariantSet>::SyncConcurrentVariant,
+>>::Replay;
+
+type AsyncConcurrentReplay<MS> = <<MS as MessageSet>::Handler as HandleAsyncConcurrentWithContext<
+    MS,
+    <MS as MessageVariantSet>::AsyncConcurrentVariant,
+>>::Replay;
+
+type SyncConcurrentRelayAndContext<MS> = (
+    Option<SyncConcurrentReplay<MS>>,
+    Context<MS, SyncConcurrentReplay<MS>>,
 );
 
+type SyncConcurrentFuture<MS> = Either<
+    std::future::Ready<Result<SyncConcurrentRelayAndContext<MS>, JoinError>>,
+    JoinHandle<SyncConcurrentRelayAndContext<MS>>,
+>;
+
+type ConcurrentMsgAndContext<MS> = Either<
+    (
+        <MS as MessageVariantSet>::SyncConcurrentVariant,
+        Context<MS, SyncConcurrentReplay<MS>>,
+    ),
+    (
+        <MS as MessageVariantSet>::AsyncConcurrentVariant,
+        Context<MS, AsyncConcurrentReplay<MS>>,
+    ),
+>;
 
 pub struct MessageSetReceiver<MS>
 where
     MS: MessageSet,
 {
-    pub receiver: mpsc::UnboundedReceiver<(
-        MessageSetItem<MS>,
-        oneshot::Sender<MessageSetReplayItem<MS>>,
-    )>,
-    pub msg_queue: Vec<(
-        MessageSetItem<MS>,
-        oneshot::Sender<MessageSetReplayItem<MS>>,
-    )>,
-    pub concurrent_msg_buf: FuturesUnordered<
-        Either<
-            std::future::Ready<Result<SyncConcurrentRelayAndSender<MS>, JoinError>>,
-            JoinHandle<SyncConcurrentRelayAndSender<MS>>,
-        >,
-    >,
+    pub receiver: mpsc::UnboundedReceiver<MsgAndReplaySender<MS>>,
+    pub msg_queue: Vec<MsgAndReplaySender<MS>>,
+    pub concurrent_msg_buf: FuturesUnordered<SyncConcurrentFuture<MS>>,
+    pub(crate) address: mpsc::WeakUnboundedSender<MsgAndReplaySender<MS>>,
+    pub(crate) state: Arc<ChannelState>,
+    pub(crate) termination: Option<TerminationReason>,
+    pub(crate) stopped: bool,
+    /// Owns the handler, held by the blocking sync concurrent handlers that borrow it.
+    pub(crate) keep_alive: Option<Arc<dyn std::any::Any + Send + Sync>>,
+}
+
+impl<MS> Drop for MessageSetReceiver<MS>
+where
+    MS: MessageSet,
+{
+    fn drop(&mut self) {
+        let reason = match self.termination.take() {
+            Some(reason) => reason,
+            None if std::thread::panicking() => TerminationReason::Panicked,
+            None => TerminationReason::Dropped,
+        };
+        self.state.terminate(reason);
+    }
 }
 
 impl<MS> MessageSetReceiver<MS>
@@ -142,73 +427,140 @@ where
             Ok(())
         }
     */
-    pub async fn recv(
-        &mut self,
-    ) -> Option<(
-        MessageSetItem<MS>,
-        oneshot::Sender<MessageSetReplayItem<MS>>,
-    )> {
+    pub async fn recv(&mut self) -> Option<MsgAndReplaySender<MS>> {
         if !self.msg_queue.is_empty() {
             self.msg_queue.pop()
         } else {
-            self.receiver.recv().await
+            let msg = self.receiver.recv().await;
+            if msg.is_none() {
+                self.termination = Some(if self.stopped {
+                    TerminationReason::Stopped
+                } else {
+                    TerminationReason::Closed
+                });
+            }
+            msg
         }
     }
+
+    pub fn id(&self) -> ChannelId {
+        self.state.id
+    }
+
+    pub fn on_terminate(&self, hook: impl FnOnce(&Terminated) + Send + 'static) {
+        self.state.on_terminate(Box::new(hook));
+    }
+
+    /// Stops accepting new messages. Already queued messages are still handled, after which
+    /// `handle_next` returns `Ok(None)`.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+        self.receiver.close();
+    }
     pub async fn handle_next(
         &mut self,
         handler: &mut MS::Handler,
     ) -> Result<Option<()>, MsgSetRecvError> {
-        if let Some((msg, replay_sender)) = self.recv().await {
-            match msg {
-                MessageSetItem::Sync(msg) => {
-                    let replay = HandleSync::handle(handler, msg);
-                    let _ = replay_sender.send(MessageSetReplayItem::Sync(replay));
-                }
-                MessageSetItem::Async(msg) => {
-                    let replay = HandleAsync::handle(handler, msg).await;
-                    let _ = replay_sender.send(MessageSetReplayItem::Async(replay));
-                }
-                MessageSetItem::SyncConcurrent(msg) => {
-                    self.handle_concurrent(handler, (Either::Left(msg), replay_sender))
-                        .await?;
-                }
-                MessageSetItem::AsyncConcurrent(msg) => {
-                    self.handle_concurrent(handler, (Either::Right(msg), replay_sender))
-                        .await?;
-                }
-            }
+        if let Some(msg) = self.recv().await {
+            self.handle_msg(handler, msg).await?;
             Ok(Some(()))
         } else {
             Ok(None)
         }
     }
 
+    pub async fn handle_msg(
+        &mut self,
+        handler: &mut MS::Handler,
+        (msg, replay_sender): MsgAndReplaySender<MS>,
+    ) -> Result<(), MsgSetRecvError> {
+        let result = self.dispatch(handler, (msg, replay_sender)).await;
+        if let Err(err) = &result {
+            self.termination = Some(TerminationReason::Failed(err.to_string()));
+        }
+        if !self.stopped && self.state.stop_requested.load(Ordering::Acquire) {
+            self.stop();
+        }
+        result
+    }
+
+    fn context<R>(&self, replay_sender: ReplyTo<R>) -> Context<MS, R> {
+        Context {
+            address: self.address.clone(),
+            state: self.state.clone(),
+            replay_sender: Some(replay_sender),
+        }
+    }
+
+    async fn dispatch(
+        &mut self,
+        handler: &mut MS::Handler,
+        (msg, replay_sender): MsgAndReplaySender<MS>,
+    ) -> Result<(), MsgSetRecvError> {
+        match msg {
+            MessageSetItem::Sync(msg) => {
+                let mut cx = self.context(replay_sender.map(MessageSetReplayItem::Sync));
+                if let Some(replay) = HandleSyncWithContext::handle(handler, msg, &mut cx) {
+                    cx.reply(replay);
+                }
+            }
+            MessageSetItem::Async(msg) => {
+                let mut cx = self.context(replay_sender.map(MessageSetReplayItem::Async));
+                if let Some(replay) = HandleAsyncWithContext::handle(handler, msg, &mut cx).await {
+                    cx.reply(replay);
+                }
+            }
+            MessageSetItem::SyncConcurrent(msg) => {
+                let cx = self.context(replay_sender.map(MessageSetReplayItem::SyncConcurrent));
+                self.handle_concurrent(handler, Either::Left((msg, cx)))
+                    .await?;
+            }
+            MessageSetItem::AsyncConcurrent(msg) => {
+                let cx = self.context(replay_sender.map(MessageSetReplayItem::AsyncConcurrent));
+                self.handle_concurrent(handler, Either::Right((msg, cx)))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits for the blocking sync concurrent handlers that are still running.
+    ///
+    /// They borrow the handler, so this must be awaited before the handler is dropped
+    /// after `handle_next` was cancelled, panicked or returned an error.
+    pub async fn flush_concurrent(&mut self) {
+        while let Some(result) = self.concurrent_msg_buf.next().await {
+            if let Ok((Some(replay), mut cx)) = result {
+                cx.reply(replay);
+            }
+        }
+    }
+
     async fn handle_concurrent(
         &mut self,
         handler: &mut MS::Handler,
-        (init_msg, sender): (
-            Either<MS::SyncConcurrentVariant, MS::AsyncConcurrentVariant>,
-            oneshot::Sender<MessageSetReplayItem<MS>>,
-        ),
+        init_msg: ConcurrentMsgAndContext<MS>,
     ) -> Result<(), MsgSetRecvError> {
         let mut async_futures = None;
         self.concurrent_msg_buf.clear();
         let handler = unsafe { &*(handler as *const MS::Handler) };
         match init_msg {
-            Either::Left(sync_msg) => {
-                self.push_sync_concurrent((sync_msg, sender), unsafe {
+            Either::Left((sync_msg, cx)) => {
+                self.push_sync_concurrent((sync_msg, cx), unsafe {
                     force_send_sync::Sync::new(handler)
                 });
             }
-            Either::Right(async_msg) => {
+            Either::Right((async_msg, cx)) => {
                 if async_futures.is_none() {
                     async_futures = Some(FuturesUnordered::new());
                 }
                 async_futures.as_mut().unwrap().push(
-                    async {
+                    async move {
+                        let mut cx = cx;
                         (
-                            HandleAsyncConcurrent::handle(handler, async_msg).await,
-                            sender,
+                            HandleAsyncConcurrentWithContext::handle(handler, async_msg, &mut cx)
+                                .await,
+                            cx,
                         )
                     }
                     .left_future(),
@@ -219,16 +571,16 @@ where
         loop {
             let (msg,replay_sender) = match self.receiver.try_recv() {
                 Ok(r) => r,
-                Err(TryRecvError::Disconnected) => {
-                    return Err(MsgSetRecvError::Disconnected);
-                }
-                Err(TryRecvError::Empty) => {
+                // the following `recv` sees that the channel is closed, after the concurrent
+                // messages that were already received are handled
+                Err(TryRecvError::Disconnected | TryRecvError::Empty) => {
                     break;
                 }
             };
             match msg {
                 MessageSetItem::SyncConcurrent(msg) => {
-                    self.push_sync_concurrent((msg, replay_sender), unsafe {
+                    let cx = self.context(replay_sender.map(MessageSetReplayItem::SyncConcurrent));
+                    self.push_sync_concurrent((msg, cx), unsafe {
                         force_send_sync::Sync::new(handler)
                     });
                 }
@@ -236,11 +588,14 @@ where
                     if async_futures.is_none() {
                         async_futures = Some(FuturesUnordered::new());
                     }
+                    let mut cx =
+                        self.context(replay_sender.map(MessageSetReplayItem::AsyncConcurrent));
                     async_futures.as_mut().unwrap().push(
-                        async {
+                        async move {
                             (
-                                HandleAsyncConcurrent::handle(handler, msg).await,
-                                replay_sender,
+                                HandleAsyncConcurrentWithContext::handle(handler, msg, &mut cx)
+                                    .await,
+                                cx,
                             )
                         }
                         .right_future(),
@@ -270,8 +625,10 @@ where
                             }
                             async_concurrent_futures = std::future::pending::<_>().right_future();
                         }
-                        Some((replay, sender)) => {
-                            let _ = sender.send(MessageSetReplayItem::AsyncConcurrent(replay));
+                        Some((replay, mut cx)) => {
+                            if let Some(replay) = replay {
+                                cx.reply(replay);
+                            }
                             async_concurrent_futures = async_futures
                                 .as_mut()
                                 .map(|n| n.next().left_future())
@@ -289,8 +646,10 @@ where
                             sync_concurrent_futures = std::future::pending::<_>().right_future();
                         }
                         Some(replay) => {
-                            let (replay, sender) = replay.map_err(|n| MsgSetRecvError::JoinError(n))?;
-                            let _ = sender.send(MessageSetReplayItem::SyncConcurrent(replay));
+                            let (replay, mut cx) = replay.map_err(MsgSetRecvError::JoinError)?;
+                            if let Some(replay) = replay {
+                                cx.reply(replay);
+                            }
                             sync_concurrent_futures = self.concurrent_msg_buf.next().left_future();
                         }
                     }
@@ -301,21 +660,23 @@ where
     }
     fn push_sync_concurrent(
         &mut self,
-        (msg, replay_sender): (
-            MS::SyncConcurrentVariant,
-            oneshot::Sender<MessageSetReplayItem<MS>>,
-        ),
+        (msg, mut cx): (MS::SyncConcurrentVariant, Context<MS, SyncConcurrentReplay<MS>>),
         handler: force_send_sync::Sync<&'static MS::Handler>,
     ) {
-        let is_blocking = HandleSyncConcurrent::is_blocking(*handler, &msg);
+        let is_blocking = HandleSyncConcurrentWithContext::is_blocking(*handler, &msg);
         if !is_blocking {
-            let replay = HandleSyncConcurrent::handle(*handler, msg);
+            let replay = HandleSyncConcurrentWithContext::handle(*handler, msg, &mut cx);
             self.concurrent_msg_buf
-                .push(ready(Ok((replay, replay_sender))).left_future());
+                .push(ready(Ok((replay, cx))).left_future());
         } else {
+            let keep_alive = self.keep_alive.clone();
             self.concurrent_msg_buf.push(
                 tokio::task::spawn_blocking(move || {
-                    (HandleSyncConcurrent::handle(*handler, msg), replay_sender)
+                    let _keep_alive = keep_alive;
+                    (
+                        HandleSyncConcurrentWithContext::handle(*handler, msg, &mut cx),
+                        cx,
+                    )
                 })
                 .right_future(),
             );
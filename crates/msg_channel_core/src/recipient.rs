@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::lifecycle::ChannelId;
+use crate::message_set::{MessageSetContains, MessageSetSender};
+use crate::reply::ReplyFuture;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("the receiver of the recipient was dropped")]
+pub struct RecipientSendError;
+
+/// The object-safe interface behind [`Recipient`], implemented by every sender whose set
+/// contains `M` with the replay `R`.
+pub trait MessageRecipient<M, R>: Send + Sync {
+    fn send(&self, msg: M) -> Result<ReplyFuture<R>, RecipientSendError>;
+
+    fn id(&self) -> ChannelId;
+}
+
+impl<MS, M> MessageRecipient<M, MS::Replay> for MessageSetSender<MS>
+where
+    MS: MessageSetContains<M>,
+    M: 'static,
+{
+    fn send(&self, msg: M) -> Result<ReplyFuture<MS::Replay>, RecipientSendError> {
+        MessageSetSender::send(self, msg).map_err(|_| RecipientSendError)
+    }
+
+    fn id(&self) -> ChannelId {
+        MessageSetSender::id(self)
+    }
+}
+
+/// Sends `M` to some message set without naming the set or its handler.
+pub struct Recipient<M, R>(Arc<dyn MessageRecipient<M, R>>);
+
+impl<M, R> Clone for Recipient<M, R> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<M, R> Recipient<M, R> {
+    pub fn new(recipient: impl MessageRecipient<M, R> + 'static) -> Self {
+        Self(Arc::new(recipient))
+    }
+
+    pub fn send(&self, msg: M) -> Result<ReplyFuture<R>, RecipientSendError> {
+        self.0.send(msg)
+    }
+
+    pub fn id(&self) -> ChannelId {
+        self.0.id()
+    }
+}
+
+impl<MS, M> From<MessageSetSender<MS>> for Recipient<M, MS::Replay>
+where
+    MS: MessageSetContains<M>,
+    M: 'static,
+{
+    fn from(sender: MessageSetSender<MS>) -> Self {
+        Self::new(sender)
+    }
+}
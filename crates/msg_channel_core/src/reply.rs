@@ -0,0 +1,190 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::stream::BoxStream;
+use futures_util::{Stream, StreamExt};
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::sync_wrapper::SyncWrapper;
+
+/// The slot a replay is sent to.
+///
+/// It can be moved to another task or forwarded to another message set with
+/// `MessageSetSender::forward`, whose handler then replies to the original caller directly.
+pub struct ReplyTo<R>(SyncWrapper<Box<dyn FnOnce(R) + Send>>);
+
+impl<R> ReplyTo<R> {
+    pub fn new(f: impl FnOnce(R) + Send + 'static) -> Self {
+        Self(SyncWrapper::new(Box::new(f)))
+    }
+
+    pub fn discard() -> Self
+    where
+        R: 'static,
+    {
+        Self::new(drop)
+    }
+
+    pub fn send(self, replay: R) {
+        (self.0.into_inner())(replay)
+    }
+
+    /// Converts the replays sent to the returned slot with `f` before they reach this one.
+    pub fn map<T>(self, f: impl FnOnce(T) -> R + Send + 'static) -> ReplyTo<T>
+    where
+        R: 'static,
+    {
+        ReplyTo::new(move |replay| self.send(f(replay)))
+    }
+}
+
+impl<R> From<oneshot::Sender<R>> for ReplyTo<R>
+where
+    R: Send + 'static,
+{
+    fn from(sender: oneshot::Sender<R>) -> Self {
+        Self::new(move |replay| {
+            let _ = sender.send(replay);
+        })
+    }
+}
+
+pub fn reply_channel<R>() -> (ReplyTo<R>, ReplyFuture<R>)
+where
+    R: Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    (
+        sender.into(),
+        ReplyFuture {
+            receiver,
+            replay: None,
+        },
+    )
+}
+
+/// Resolves to the replay of a message.
+///
+/// Panics if the [`ReplyTo`] is dropped without a replay. When the replay is a [`ReplyStream`],
+/// this is also a stream of its items.
+pub struct ReplyFuture<R> {
+    receiver: oneshot::Receiver<R>,
+    replay: Option<R>,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("the replay was dropped without being sent")]
+pub struct ReplyDropped;
+
+impl<R> ReplyFuture<R> {
+    /// Like awaiting the future, but fails instead of panicking when there is no replay, e.g.
+    /// because the handler panicked or the receiver was dropped.
+    pub async fn try_replay(mut self) -> Result<R, ReplyDropped> {
+        match self.replay.take() {
+            Some(replay) => Ok(replay),
+            None => (&mut self.receiver).await.map_err(|_| ReplyDropped),
+        }
+    }
+
+    fn poll_replay(&mut self, cx: &mut Context<'_>) -> Poll<R> {
+        if let Some(replay) = self.replay.take() {
+            return Poll::Ready(replay);
+        }
+        Pin::new(&mut self.receiver)
+            .poll(cx)
+            .map(|replay| replay.expect("the replay was dropped without being sent"))
+    }
+}
+
+impl<R> Unpin for ReplyFuture<R> {}
+
+impl<R> Future for ReplyFuture<R> {
+    type Output = R;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.poll_replay(cx)
+    }
+}
+
+impl<T> Stream for ReplyFuture<ReplyStream<T>> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut stream = match self.poll_replay(cx) {
+            Poll::Ready(stream) => stream,
+            Poll::Pending => return Poll::Pending,
+        };
+        let item = stream.poll_next_unpin(cx);
+        self.replay = Some(stream);
+        item
+    }
+}
+
+/// A replay that is a stream of items, for handlers of any category.
+///
+/// Dropping it cancels the stream: [`ReplySink::send`] fails and [`ReplySink::closed`] resolves,
+/// so the producer can stop early.
+pub struct ReplyStream<T>(ReplyStreamInner<T>);
+
+enum ReplyStreamInner<T> {
+    Channel(mpsc::Receiver<T>),
+    Stream(BoxStream<'static, T>),
+}
+
+impl<T> ReplyStream<T> {
+    /// Creates a stream whose items are sent through the returned sink.
+    ///
+    /// At most `buffer` items are buffered, at least one, after which `ReplySink::send` waits for
+    /// the caller to catch up.
+    pub fn channel(buffer: usize) -> (ReplySink<T>, Self) {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        (ReplySink(sender), Self(ReplyStreamInner::Channel(receiver)))
+    }
+
+    pub fn from_stream(stream: impl Stream<Item = T> + Send + 'static) -> Self {
+        Self(ReplyStreamInner::Stream(stream.boxed()))
+    }
+}
+
+impl<T> Stream for ReplyStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        match &mut self.0 {
+            ReplyStreamInner::Channel(receiver) => receiver.poll_recv(cx),
+            ReplyStreamInner::Stream(stream) => stream.poll_next_unpin(cx),
+        }
+    }
+}
+
+/// The sending half of [`ReplyStream::channel`]. The stream ends once every sink is dropped.
+pub struct ReplySink<T>(mpsc::Sender<T>);
+
+impl<T> Clone for ReplySink<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> ReplySink<T> {
+    /// Waits for buffer space and sends `item`, fails if the stream was dropped.
+    pub async fn send(&self, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        self.0.send(item).await
+    }
+
+    /// Sends `item` from outside of async code, e.g. a blocking sync handler.
+    pub fn blocking_send(&self, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        self.0.blocking_send(item)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Resolves once the stream was dropped.
+    pub async fn closed(&self) {
+        self.0.closed().await
+    }
+}
@@ -0,0 +1,56 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use tokio::sync::mpsc;
+
+/// A stream of chunks carried by a message, e.g. an upload, created with
+/// `MessageSetSender::send_stream`.
+///
+/// It ends once the caller dropped its [`RequestSink`].
+pub struct RequestStream<T>(mpsc::Receiver<T>);
+
+impl<T> RequestStream<T> {
+    /// Creates a stream fed by the returned sink, which buffers at most `buffer` chunks, at
+    /// least one.
+    pub fn channel(buffer: usize) -> (RequestSink<T>, Self) {
+        let (sender, receiver) = mpsc::channel(buffer.max(1));
+        (RequestSink(sender), Self(receiver))
+    }
+}
+
+impl<T> Stream for RequestStream<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll_recv(cx)
+    }
+}
+
+/// Feeds a [`RequestStream`]. Dropping every sink ends the stream.
+pub struct RequestSink<T>(mpsc::Sender<T>);
+
+impl<T> Clone for RequestSink<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> RequestSink<T> {
+    /// Waits until the handler caught up and sends `item`, fails if the handler dropped the
+    /// stream.
+    pub async fn send(&self, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        self.0.send(item).await
+    }
+
+    pub fn blocking_send(&self, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        self.0.blocking_send(item)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+
+    /// Ends the stream. The same as dropping the sink, unless it was cloned.
+    pub fn finish(self) {}
+}
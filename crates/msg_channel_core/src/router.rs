@@ -0,0 +1,369 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use futures_util::future::LocalBoxFuture;
+use futures_util::stream::FuturesUnordered;
+use futures_util::StreamExt;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::any::{AnyMessage, AnyValue};
+use crate::handle::{HandleAsync, HandleAsyncConcurrent, HandleSync, HandleSyncConcurrent};
+use crate::reply::{reply_channel, ReplyFuture, ReplyTo};
+
+#[derive(Error, Debug)]
+pub enum RouterError {
+    /// No handler was registered for the type of the message, it is given back.
+    #[error("Unhandled")]
+    Unhandled(AnyValue),
+    #[error("Disconnected")]
+    Disconnected,
+    #[error("ReplyDropped")]
+    ReplyDropped,
+    /// The replay is not of the requested type, it is given back.
+    #[error("WrongReplyType")]
+    WrongReplyType(AnyValue),
+}
+
+pub type RouterReplay = Result<AnyMessage, RouterError>;
+
+/// A message type was registered with more than one handler.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("{message} is registered twice")]
+pub struct DuplicateRoute {
+    /// The name of the message type.
+    pub message: &'static str,
+}
+
+type RouterEnvelope = (AnyMessage, ReplyTo<RouterReplay>);
+
+type HandleFn =
+    dyn for<'a> Fn(&'a mut (dyn Any + Send), AnyMessage) -> LocalBoxFuture<'a, AnyMessage> + Send;
+
+type HandleConcurrentFn =
+    dyn for<'a> Fn(&'a (dyn Any + Send), AnyMessage) -> LocalBoxFuture<'a, AnyMessage> + Send;
+
+enum RouteHandle {
+    Exclusive(Box<HandleFn>),
+    /// Directly following concurrent messages are handled together, like in a message set.
+    Concurrent(Box<HandleConcurrentFn>),
+}
+
+struct Route {
+    handler: usize,
+    handle: RouteHandle,
+}
+
+#[derive(Default)]
+struct Routes {
+    by_type: HashMap<TypeId, Route>,
+    /// The first message type registered twice, reported by `build`.
+    duplicate: Option<&'static str>,
+}
+
+/// Registers handlers by the [`TypeId`] of their messages at runtime, instead of a `#[msg_set]`.
+#[derive(Default)]
+pub struct RouterBuilder {
+    handlers: Vec<Box<dyn Any + Send>>,
+    routes: Routes,
+}
+
+impl RouterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `handler`, `register` picks the messages it handles. A message type can only be
+    /// registered once, `build` fails otherwise.
+    pub fn handler<H>(
+        mut self,
+        handler: H,
+        register: impl FnOnce(&mut HandlerRoutes<'_, H>),
+    ) -> Self
+    where
+        H: Send + 'static,
+    {
+        self.handlers.push(Box::new(handler));
+        register(&mut HandlerRoutes {
+            handler: self.handlers.len() - 1,
+            routes: &mut self.routes,
+            _marker: PhantomData,
+        });
+        self
+    }
+
+    /// Whether a handler was registered for the message type `type_id`.
+    pub fn is_routed(&self, type_id: TypeId) -> bool {
+        self.routes.by_type.contains_key(&type_id)
+    }
+
+    pub fn build(self) -> Result<(RouterSender, RouterReceiver), DuplicateRoute> {
+        if let Some(message) = self.routes.duplicate {
+            return Err(DuplicateRoute { message });
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Ok((
+            RouterSender { sender },
+            RouterReceiver {
+                receiver,
+                pending: None,
+                handlers: self.handlers,
+                routes: self.routes.by_type,
+            },
+        ))
+    }
+}
+
+pub struct HandlerRoutes<'a, H> {
+    handler: usize,
+    routes: &'a mut Routes,
+    _marker: PhantomData<fn(H)>,
+}
+
+impl<H> HandlerRoutes<'_, H>
+where
+    H: 'static,
+{
+    pub fn handle_sync<M: 'static>(&mut self) -> &mut Self
+    where
+        H: HandleSync<M>,
+    {
+        self.on_sync(|handler: &mut H, msg: M| HandleSync::handle(handler, msg))
+    }
+
+    pub fn handle_async<M: 'static>(&mut self) -> &mut Self
+    where
+        H: HandleAsync<M>,
+    {
+        self.route::<M>(RouteHandle::Exclusive(Box::new(|handler, msg| {
+            let (handler, msg) = downcast::<H, M>(handler, msg);
+            Box::pin(async move {
+                let replay: AnyMessage = Box::new(HandleAsync::handle(handler, msg).await);
+                replay
+            })
+        })))
+    }
+
+    pub fn handle_async_concurrent<M: 'static>(&mut self) -> &mut Self
+    where
+        H: HandleAsyncConcurrent<M>,
+    {
+        self.route::<M>(RouteHandle::Concurrent(Box::new(|handler, msg| {
+            let (handler, msg) = downcast_ref::<H, M>(handler, msg);
+            Box::pin(async move {
+                let replay: AnyMessage =
+                    Box::new(HandleAsyncConcurrent::handle(handler, msg).await);
+                replay
+            })
+        })))
+    }
+
+    /// Sync concurrent handlers run on the router task, not on the blocking thread pool.
+    pub fn handle_sync_concurrent<M: 'static>(&mut self) -> &mut Self
+    where
+        H: HandleSyncConcurrent<M>,
+    {
+        self.on_sync_concurrent(|handler: &H, msg: M| HandleSyncConcurrent::handle(handler, msg))
+    }
+
+    /// Like `handle_sync`, with a closure instead of a `HandleSync` impl.
+    pub fn on_sync<M, R>(&mut self, f: impl Fn(&mut H, M) -> R + Send + 'static) -> &mut Self
+    where
+        M: 'static,
+        R: Send + 'static,
+    {
+        self.route::<M>(RouteHandle::Exclusive(Box::new(move |handler, msg| {
+            let (handler, msg) = downcast::<H, M>(handler, msg);
+            let replay: AnyMessage = Box::new(f(handler, msg));
+            Box::pin(std::future::ready(replay))
+        })))
+    }
+
+    pub fn on_async<M, R>(
+        &mut self,
+        f: impl AsyncFn(&mut H, M) -> R + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        M: 'static,
+        R: Send + 'static,
+    {
+        let f = Arc::new(f);
+        self.route::<M>(RouteHandle::Exclusive(Box::new(move |handler, msg| {
+            let (handler, msg) = downcast::<H, M>(handler, msg);
+            let f = f.clone();
+            Box::pin(async move {
+                let replay: AnyMessage = Box::new(f(handler, msg).await);
+                replay
+            })
+        })))
+    }
+
+    pub fn on_async_concurrent<M, R>(
+        &mut self,
+        f: impl AsyncFn(&H, M) -> R + Send + Sync + 'static,
+    ) -> &mut Self
+    where
+        M: 'static,
+        R: Send + 'static,
+    {
+        let f = Arc::new(f);
+        self.route::<M>(RouteHandle::Concurrent(Box::new(move |handler, msg| {
+            let (handler, msg) = downcast_ref::<H, M>(handler, msg);
+            let f = f.clone();
+            Box::pin(async move {
+                let replay: AnyMessage = Box::new(f(handler, msg).await);
+                replay
+            })
+        })))
+    }
+
+    pub fn on_sync_concurrent<M, R>(&mut self, f: impl Fn(&H, M) -> R + Send + 'static) -> &mut Self
+    where
+        M: 'static,
+        R: Send + 'static,
+    {
+        self.route::<M>(RouteHandle::Concurrent(Box::new(move |handler, msg| {
+            let (handler, msg) = downcast_ref::<H, M>(handler, msg);
+            let replay: AnyMessage = Box::new(f(handler, msg));
+            Box::pin(std::future::ready(replay))
+        })))
+    }
+
+    fn route<M: 'static>(&mut self, handle: RouteHandle) -> &mut Self {
+        let route = Route {
+            handler: self.handler,
+            handle,
+        };
+        match self.routes.by_type.entry(TypeId::of::<M>()) {
+            Entry::Vacant(entry) => {
+                entry.insert(route);
+            }
+            Entry::Occupied(_) => {
+                self.routes.duplicate.get_or_insert(type_name::<M>());
+            }
+        }
+        self
+    }
+}
+
+fn downcast<H: 'static, M: 'static>(
+    handler: &mut (dyn Any + Send),
+    msg: AnyMessage,
+) -> (&mut H, M) {
+    let handler = handler
+        .downcast_mut()
+        .expect("routes point at handlers of their type");
+    (handler, downcast_msg(msg))
+}
+
+fn downcast_ref<H: 'static, M: 'static>(handler: &(dyn Any + Send), msg: AnyMessage) -> (&H, M) {
+    let handler = handler
+        .downcast_ref()
+        .expect("routes point at handlers of their type");
+    (handler, downcast_msg(msg))
+}
+
+fn downcast_msg<M: 'static>(msg: AnyMessage) -> M {
+    *msg.downcast()
+        .expect("routes are keyed by the type of their message")
+}
+
+#[derive(Clone)]
+pub struct RouterSender {
+    sender: mpsc::UnboundedSender<RouterEnvelope>,
+}
+
+impl RouterSender {
+    pub fn send_any(&self, msg: AnyMessage) -> Result<ReplyFuture<RouterReplay>, RouterError> {
+        let (reply_to, replay) = reply_channel();
+        self.sender
+            .send((msg, reply_to))
+            .map_err(|_| RouterError::Disconnected)?;
+        Ok(replay)
+    }
+
+    /// Sends `msg` and downcasts its replay to `R`.
+    pub async fn request<M, R>(&self, msg: M) -> Result<R, RouterError>
+    where
+        M: Send + 'static,
+        R: 'static,
+    {
+        let replay = self
+            .send_any(Box::new(msg))?
+            .try_replay()
+            .await
+            .map_err(|_| RouterError::ReplyDropped)??;
+        AnyValue::new(replay)
+            .downcast()
+            .map_err(RouterError::WrongReplyType)
+    }
+}
+
+/// Handles the messages of a [`RouterSender`].
+///
+/// Its futures are not `Send`, since the futures of generic `HandleAsync` handlers can't be
+/// known to be, so it runs on the current task or a `LocalSet`.
+pub struct RouterReceiver {
+    receiver: mpsc::UnboundedReceiver<RouterEnvelope>,
+    /// Received while collecting concurrent messages, handled next.
+    pending: Option<RouterEnvelope>,
+    handlers: Vec<Box<dyn Any + Send>>,
+    routes: HashMap<TypeId, Route>,
+}
+
+impl RouterReceiver {
+    /// Handles the next message, `None` once every sender was dropped.
+    pub async fn handle_next(&mut self) -> Option<()> {
+        let (msg, reply_to) = match self.pending.take() {
+            Some(envelope) => envelope,
+            None => self.receiver.recv().await?,
+        };
+        let Some(route) = self.routes.get(&(*msg).type_id()) else {
+            reply_to.send(Err(RouterError::Unhandled(AnyValue::new(msg))));
+            return Some(());
+        };
+        match &route.handle {
+            RouteHandle::Exclusive(handle) => {
+                let replay = handle(self.handlers[route.handler].as_mut(), msg).await;
+                reply_to.send(Ok(replay));
+            }
+            RouteHandle::Concurrent(_) => {
+                let mut batch = vec![(msg, reply_to)];
+                while let Ok(envelope) = self.receiver.try_recv() {
+                    if self.is_concurrent(&envelope.0) {
+                        batch.push(envelope);
+                    } else {
+                        self.pending = Some(envelope);
+                        break;
+                    }
+                }
+                let mut replays: FuturesUnordered<_> = batch
+                    .into_iter()
+                    .map(|(msg, reply_to)| {
+                        let route = &self.routes[&(*msg).type_id()];
+                        let RouteHandle::Concurrent(handle) = &route.handle else {
+                            unreachable!()
+                        };
+                        let replay = handle(self.handlers[route.handler].as_ref(), msg);
+                        async move { reply_to.send(Ok(replay.await)) }
+                    })
+                    .collect();
+                while replays.next().await.is_some() {}
+            }
+        }
+        Some(())
+    }
+
+    fn is_concurrent(&self, msg: &AnyMessage) -> bool {
+        self.routes
+            .get(&(**msg).type_id())
+            .is_some_and(|route| matches!(route.handle, RouteHandle::Concurrent(_)))
+    }
+
+    pub async fn run(mut self) {
+        while self.handle_next().await.is_some() {}
+    }
+}
@@ -0,0 +1,58 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::{Stream, StreamExt};
+use tokio::sync::mpsc;
+
+use crate::request::{RequestSink, RequestStream};
+
+/// One end of a duplex conversation opened with `MessageSetSender::open_session`, sending `Tx`
+/// and receiving `Rx`.
+///
+/// The caller holds a `Session<In, Out>` and the handler a `Session<Out, In>`. The session is
+/// independent of the message queue and lasts until either end is dropped.
+pub struct Session<Tx, Rx> {
+    sink: RequestSink<Tx>,
+    stream: RequestStream<Rx>,
+}
+
+impl<Tx, Rx> Session<Tx, Rx> {
+    /// Creates both ends, each direction buffers at most `buffer` messages, at least one.
+    pub fn pair(buffer: usize) -> (Self, Session<Rx, Tx>) {
+        let (tx_sink, tx_stream) = RequestStream::channel(buffer);
+        let (rx_sink, rx_stream) = RequestStream::channel(buffer);
+        (
+            Self {
+                sink: tx_sink,
+                stream: rx_stream,
+            },
+            Session {
+                sink: rx_sink,
+                stream: tx_stream,
+            },
+        )
+    }
+
+    /// Waits until the other end caught up and sends `msg`, fails if it was dropped.
+    pub async fn send(&self, msg: Tx) -> Result<(), mpsc::error::SendError<Tx>> {
+        self.sink.send(msg).await
+    }
+
+    /// The next message of the other end, `None` once it was dropped.
+    pub async fn recv(&mut self) -> Option<Rx> {
+        self.stream.next().await
+    }
+
+    /// Splits the session, so sending and receiving can happen in different tasks.
+    pub fn split(self) -> (RequestSink<Tx>, RequestStream<Rx>) {
+        (self.sink, self.stream)
+    }
+}
+
+impl<Tx, Rx> Stream for Session<Tx, Rx> {
+    type Item = Rx;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Rx>> {
+        self.stream.poll_next_unpin(cx)
+    }
+}
@@ -0,0 +1,286 @@
+use std::any::Any;
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::{select, Either, LocalBoxFuture};
+use futures_util::stream::FuturesUnordered;
+use futures_util::{FutureExt, StreamExt};
+use thiserror::Error;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+use crate::lifecycle::TerminationReason;
+use crate::message_set::{MessageSet, MessageSetReceiver, MessageSetSender, MsgSetRecvError};
+use crate::msg_channel;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestartStrategy {
+    /// Only the failed handler is restarted.
+    OneForOne,
+    /// All handlers of the supervisor are restarted when one of them fails.
+    OneForAll,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backoff {
+    None,
+    Fixed(Duration),
+    /// `base * 2^n`, where `n` is the number of restarts inside the intensity window.
+    Exponential { base: Duration, max: Duration },
+}
+
+impl Backoff {
+    fn delay(&self, restarts: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { base, max } => base
+                .saturating_mul(2u32.saturating_pow(restarts))
+                .min(max),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum ChildFailure {
+    #[error("Panicked {0}")]
+    Panicked(String),
+    #[error("RecvError {0}")]
+    RecvError(MsgSetRecvError),
+}
+
+impl From<&ChildFailure> for TerminationReason {
+    fn from(failure: &ChildFailure) -> Self {
+        match failure {
+            ChildFailure::Panicked(_) => TerminationReason::Panicked,
+            ChildFailure::RecvError(err) => TerminationReason::Failed(err.to_string()),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SupervisorError {
+    #[error("Restart intensity exceeded, last failure: {0}")]
+    RestartIntensityExceeded(ChildFailure),
+}
+
+enum ChildExit {
+    Completed,
+    Stopped,
+    Failed(ChildFailure),
+}
+
+type ChildRun = LocalBoxFuture<'static, (Box<dyn SupervisedChild>, ChildExit)>;
+
+trait SupervisedChild {
+    fn run(self: Box<Self>, stop: watch::Receiver<u64>, delay: Duration) -> ChildRun;
+
+    fn terminate(self: Box<Self>, reason: TerminationReason);
+}
+
+struct Child<MS, F>
+where
+    MS: MessageSet,
+{
+    receiver: MessageSetReceiver<MS>,
+    factory: F,
+}
+
+impl<MS, F> Child<MS, F>
+where
+    MS: MessageSet,
+{
+    async fn handle_until_stop(
+        &mut self,
+        handler: &mut MS::Handler,
+        stop: &mut watch::Receiver<u64>,
+    ) -> Result<ChildExit, MsgSetRecvError> {
+        loop {
+            // only wait for the stop signal between messages, so a sibling restart never
+            // drops a message that is in the middle of being handled
+            let msg = match select(pin!(stop.changed()), pin!(self.receiver.recv())).await {
+                Either::Left(_) => return Ok(ChildExit::Stopped),
+                Either::Right((Some(msg), _)) => msg,
+                Either::Right((None, _)) => return Ok(ChildExit::Completed),
+            };
+            self.receiver.handle_msg(handler, msg).await?;
+        }
+    }
+}
+
+impl<MS, F> SupervisedChild for Child<MS, F>
+where
+    MS: MessageSet,
+    MS::Handler: Send,
+    F: FnMut() -> MS::Handler + 'static,
+{
+    fn run(mut self: Box<Self>, mut stop: watch::Receiver<u64>, delay: Duration) -> ChildRun {
+        async move {
+            if !delay.is_zero() {
+                let sleep = pin!(tokio::time::sleep(delay));
+                if let Either::Left(_) = select(pin!(stop.changed()), sleep).await {
+                    return (self as Box<dyn SupervisedChild>, ChildExit::Stopped);
+                }
+            }
+            // the blocking sync concurrent handlers borrow the handler, so they hold it as well,
+            // in case this future is dropped while they run
+            // SAFETY: the handler is `Send` and `Sync`, and the other owners only drop it
+            let owner = Arc::new(unsafe {
+                force_send_sync::SendSync::new(UnsafeCell::new((self.factory)()))
+            });
+            self.receiver.keep_alive = Some(owner.clone());
+            // SAFETY: this is the only reference taken from the cell
+            let handler = unsafe { &mut *owner.get() };
+            let result = AssertUnwindSafe(self.handle_until_stop(handler, &mut stop))
+                .catch_unwind()
+                .await;
+            self.receiver.flush_concurrent().await;
+            self.receiver.keep_alive = None;
+            drop(owner);
+            let exit = match result {
+                Ok(Ok(exit)) => exit,
+                Ok(Err(err)) => ChildExit::Failed(ChildFailure::RecvError(err)),
+                Err(payload) => ChildExit::Failed(ChildFailure::Panicked(panic_message(payload))),
+            };
+            (self as Box<dyn SupervisedChild>, exit)
+        }
+        .boxed_local()
+    }
+
+    fn terminate(mut self: Box<Self>, reason: TerminationReason) {
+        self.receiver.termination = Some(reason);
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Owns the receivers of its children and restarts their handler loops when they panic or
+/// return a [`MsgSetRecvError`].
+///
+/// The receivers outlive every restart, so the [`MessageSetSender`]s stay valid.
+pub struct Supervisor {
+    strategy: RestartStrategy,
+    max_restarts: usize,
+    within: Duration,
+    backoff: Backoff,
+    children: Vec<Box<dyn SupervisedChild>>,
+}
+
+impl Supervisor {
+    pub fn new(strategy: RestartStrategy) -> Self {
+        Self {
+            strategy,
+            max_restarts: 3,
+            within: Duration::from_secs(5),
+            backoff: Backoff::None,
+            children: vec![],
+        }
+    }
+
+    /// Gives up once more than `max_restarts` restarts happen within `within`.
+    pub fn restart_intensity(mut self, max_restarts: usize, within: Duration) -> Self {
+        self.max_restarts = max_restarts;
+        self.within = within;
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn supervise<MS, F>(&mut self, factory: F) -> MessageSetSender<MS>
+    where
+        MS: MessageSet,
+        MS::Handler: Send,
+        F: FnMut() -> MS::Handler + 'static,
+    {
+        let (sender, receiver) = msg_channel::<MS>();
+        self.supervise_receiver(receiver, factory);
+        sender
+    }
+
+    pub fn supervise_receiver<MS, F>(&mut self, receiver: MessageSetReceiver<MS>, factory: F)
+    where
+        MS: MessageSet,
+        MS::Handler: Send,
+        F: FnMut() -> MS::Handler + 'static,
+    {
+        self.children.push(Box::new(Child { receiver, factory }));
+    }
+
+    /// Runs the children until all of their channels are closed.
+    ///
+    /// The future is not `Send`, since the futures of generic `HandleAsync` handlers can't be
+    /// known to be, so it runs on the current task or a `LocalSet`.
+    pub async fn run(self) -> Result<(), SupervisorError> {
+        let (stop_sender, _) = watch::channel(0u64);
+        let mut running: FuturesUnordered<ChildRun> = self
+            .children
+            .into_iter()
+            .map(|child| child.run(stop_sender.subscribe(), Duration::ZERO))
+            .collect();
+        let mut restarts: VecDeque<Instant> = VecDeque::new();
+
+        while let Some((child, exit)) = running.next().await {
+            let failure = match exit {
+                ChildExit::Completed | ChildExit::Stopped => continue,
+                ChildExit::Failed(failure) => failure,
+            };
+            let now = Instant::now();
+            while restarts
+                .front()
+                .is_some_and(|at| now.duration_since(*at) > self.within)
+            {
+                restarts.pop_front();
+            }
+            if restarts.len() >= self.max_restarts {
+                child.terminate((&failure).into());
+                stop_sender.send_modify(|generation| *generation += 1);
+                while let Some((child, exit)) = running.next().await {
+                    match exit {
+                        ChildExit::Completed => {}
+                        ChildExit::Stopped => child.terminate(TerminationReason::Stopped),
+                        ChildExit::Failed(failure) => child.terminate((&failure).into()),
+                    }
+                }
+                return Err(SupervisorError::RestartIntensityExceeded(failure));
+            }
+            let delay = self.backoff.delay(restarts.len() as u32);
+            restarts.push_back(now);
+
+            match self.strategy {
+                RestartStrategy::OneForOne => {
+                    running.push(child.run(stop_sender.subscribe(), delay));
+                }
+                RestartStrategy::OneForAll => {
+                    stop_sender.send_modify(|generation| *generation += 1);
+                    let mut children = vec![child];
+                    while let Some((child, exit)) = running.next().await {
+                        if !matches!(exit, ChildExit::Completed) {
+                            children.push(child);
+                        }
+                    }
+                    running.extend(
+                        children
+                            .into_iter()
+                            .map(|child| child.run(stop_sender.subscribe(), delay)),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,18 @@
+/// Makes a value `Sync` by never handing out a shared reference to it.
+///
+/// The value is only reachable through an owned or a mutable wrapper, both of which are
+/// exclusive, so sharing `&SyncWrapper<T>` between threads can't touch the value.
+pub(crate) struct SyncWrapper<T>(T);
+
+// SAFETY: no method takes `&self`, so a shared reference gives no access to the value
+unsafe impl<T> Sync for SyncWrapper<T> {}
+
+impl<T> SyncWrapper<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub(crate) fn into_inner(self) -> T {
+        self.0
+    }
+}
@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::{select, Either};
+use tokio::sync::mpsc;
+use tokio::time::{sleep_until, Instant};
+
+const LEVEL_BITS: usize = 6;
+const SLOTS: usize = 1 << LEVEL_BITS;
+const SLOT_MASK: u64 = SLOTS as u64 - 1;
+const NUM_LEVELS: usize = 6;
+// in milliseconds, a bit more than two years
+const MAX_DURATION: u64 = (1 << (LEVEL_BITS * NUM_LEVELS)) - 1;
+const MAX_DELAY: Duration = Duration::from_millis(MAX_DURATION);
+
+enum EntryKind {
+    Once(Box<dyn FnOnce() + Send>),
+    /// The callback returns `false` once the interval should stop.
+    Interval {
+        period: u64,
+        tick: Box<dyn FnMut() -> bool + Send>,
+    },
+}
+
+struct Entry {
+    id: u64,
+    when: u64,
+    cancelled: Arc<AtomicBool>,
+    kind: EntryKind,
+}
+
+enum Command {
+    Insert(Instant, Entry),
+    Cancel(u64),
+}
+
+/// A cancellable timer scheduled by `MessageSetSender::send_after`, `send_at` or
+/// `send_interval`.
+///
+/// Dropping the handle does not cancel the timer.
+pub struct TimerHandle {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl TimerHandle {
+    pub fn cancel(&self) {
+        if !self.cancelled.swap(true, Ordering::AcqRel) {
+            let _ = self.commands.send(Command::Cancel(self.id));
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+}
+
+/// The timers of one channel, driven by a single task that is spawned on first use.
+pub(crate) struct TimerWheel {
+    commands: mpsc::UnboundedSender<Command>,
+    next_id: AtomicU64,
+}
+
+impl TimerWheel {
+    pub(crate) fn start() -> Self {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(drive(receiver));
+        Self {
+            commands,
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn once(&self, deadline: Instant, f: impl FnOnce() + Send + 'static) -> TimerHandle {
+        self.schedule(deadline, EntryKind::Once(Box::new(f)))
+    }
+
+    pub(crate) fn interval(
+        &self,
+        period: Duration,
+        tick: impl FnMut() -> bool + Send + 'static,
+    ) -> TimerHandle {
+        let kind = EntryKind::Interval {
+            period: (period.min(MAX_DELAY).as_millis() as u64).max(1),
+            tick: Box::new(tick),
+        };
+        self.schedule(deadline_after(period), kind)
+    }
+
+    fn schedule(&self, deadline: Instant, kind: EntryKind) -> TimerHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let entry = Entry {
+            id,
+            when: 0,
+            cancelled: cancelled.clone(),
+            kind,
+        };
+        let _ = self.commands.send(Command::Insert(deadline, entry));
+        TimerHandle {
+            id,
+            cancelled,
+            commands: self.commands.clone(),
+        }
+    }
+}
+
+async fn drive(mut commands: mpsc::UnboundedReceiver<Command>) {
+    let start = Instant::now();
+    let mut wheel = Wheel::new();
+    let mut closed = false;
+    loop {
+        let now = elapsed_ms(start, Instant::now());
+        wheel.advance(now);
+        if closed && wheel.is_empty() {
+            return;
+        }
+
+        let next = wheel
+            .next_expiration()
+            .map(|(_, _, deadline)| start + Duration::from_millis(deadline));
+        let command = match (closed, next) {
+            (false, Some(deadline)) => {
+                match select(pin!(commands.recv()), pin!(sleep_until(deadline))).await {
+                    Either::Left((command, _)) => command,
+                    Either::Right(_) => continue,
+                }
+            }
+            (false, None) => commands.recv().await,
+            (true, Some(deadline)) => {
+                sleep_until(deadline).await;
+                continue;
+            }
+            (true, None) => return,
+        };
+        match command {
+            Some(Command::Insert(deadline, mut entry)) => {
+                entry.when = elapsed_ms(start, deadline);
+                wheel.insert(entry);
+            }
+            Some(Command::Cancel(id)) => wheel.remove(id),
+            None => closed = true,
+        }
+    }
+}
+
+/// `delay` from now, delays longer than the wheel can hold are shortened to its longest.
+pub(crate) fn deadline_after(delay: Duration) -> Instant {
+    let now = Instant::now();
+    let max = now + MAX_DELAY;
+    now.checked_add(delay).map_or(max, |deadline| deadline.min(max))
+}
+
+// rounds up, so a timer never fires before its deadline
+fn elapsed_ms(start: Instant, instant: Instant) -> u64 {
+    instant
+        .saturating_duration_since(start)
+        .as_nanos()
+        .div_ceil(1_000_000) as u64
+}
+
+struct Level {
+    occupied: u64,
+    slots: [Vec<Entry>; SLOTS],
+}
+
+/// A hierarchical timing wheel with a resolution of one millisecond.
+///
+/// Level `n` has 64 slots that each cover `64^n` milliseconds. Timers are placed on the level of
+/// the most significant digit in which their deadline differs from `elapsed`, and cascade down to
+/// lower levels as time advances.
+struct Wheel {
+    now: u64,
+    elapsed: u64,
+    levels: Vec<Level>,
+    locations: HashMap<u64, (usize, usize)>,
+}
+
+impl Wheel {
+    fn new() -> Self {
+        Self {
+            now: 0,
+            elapsed: 0,
+            levels: (0..NUM_LEVELS)
+                .map(|_| Level {
+                    occupied: 0,
+                    slots: std::array::from_fn(|_| vec![]),
+                })
+                .collect(),
+            locations: HashMap::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    fn insert(&mut self, mut entry: Entry) {
+        if entry.when <= self.elapsed {
+            self.fire(entry);
+            return;
+        }
+        // a deadline of `send_at` can be further away than the wheel reaches
+        entry.when = entry.when.min(self.elapsed + MAX_DURATION);
+        let level = level_for(self.elapsed, entry.when);
+        let slot = ((entry.when >> (level * LEVEL_BITS)) & SLOT_MASK) as usize;
+        self.locations.insert(entry.id, (level, slot));
+        let level = &mut self.levels[level];
+        level.occupied |= 1 << slot;
+        level.slots[slot].push(entry);
+    }
+
+    fn remove(&mut self, id: u64) {
+        let Some((level, slot)) = self.locations.remove(&id) else {
+            return;
+        };
+        let level = &mut self.levels[level];
+        level.slots[slot].retain(|entry| entry.id != id);
+        if level.slots[slot].is_empty() {
+            level.occupied &= !(1 << slot);
+        }
+    }
+
+    fn next_expiration(&self) -> Option<(usize, usize, u64)> {
+        self.levels
+            .iter()
+            .enumerate()
+            .find_map(|(level_index, level)| {
+                if level.occupied == 0 {
+                    return None;
+                }
+                let slot_range = 1u64 << (level_index * LEVEL_BITS);
+                let level_range = slot_range << LEVEL_BITS;
+                let now_slot = (self.elapsed / slot_range) & SLOT_MASK;
+                let slot = (level
+                    .occupied
+                    .rotate_right(now_slot as u32)
+                    .trailing_zeros() as u64
+                    + now_slot)
+                    & SLOT_MASK;
+                let mut deadline = (self.elapsed & !(level_range - 1)) + slot * slot_range;
+                if deadline <= self.elapsed && level_index > 0 {
+                    deadline += level_range;
+                }
+                Some((level_index, slot as usize, deadline))
+            })
+    }
+
+    fn advance(&mut self, now: u64) {
+        self.now = now;
+        while let Some((level, slot, deadline)) = self.next_expiration() {
+            if deadline > now {
+                break;
+            }
+            self.elapsed = self.elapsed.max(deadline);
+            let level = &mut self.levels[level];
+            level.occupied &= !(1 << slot);
+            for entry in std::mem::take(&mut level.slots[slot]) {
+                self.locations.remove(&entry.id);
+                self.insert(entry);
+            }
+        }
+        self.elapsed = self.elapsed.max(now);
+    }
+
+    fn fire(&mut self, entry: Entry) {
+        if entry.cancelled.load(Ordering::Acquire) {
+            return;
+        }
+        match entry.kind {
+            EntryKind::Once(f) => f(),
+            EntryKind::Interval { period, mut tick } => {
+                if tick() && !entry.cancelled.load(Ordering::Acquire) {
+                    // missed ticks are skipped rather than fired in a burst
+                    let when = (entry.when + period).max(self.now + 1);
+                    self.insert(Entry {
+                        when,
+                        kind: EntryKind::Interval { period, tick },
+                        ..entry
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn level_for(elapsed: u64, when: u64) -> usize {
+    let masked = ((elapsed ^ when) | SLOT_MASK).min(MAX_DURATION - 1);
+    let significant = 63 - masked.leading_zeros() as usize;
+    significant / LEVEL_BITS
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    type Fired = Arc<Mutex<Vec<u64>>>;
+
+    fn once(id: u64, when: u64, fired: &Fired) -> Entry {
+        let fired = fired.clone();
+        Entry {
+            id,
+            when,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            kind: EntryKind::Once(Box::new(move || fired.lock().unwrap().push(id))),
+        }
+    }
+
+    fn take(fired: &Fired) -> Vec<u64> {
+        std::mem::take(&mut *fired.lock().unwrap())
+    }
+
+    // deadlines on both sides of every level boundary
+    fn boundaries() -> Vec<u64> {
+        let mut deadlines = vec![1, 2];
+        for level in 1..NUM_LEVELS {
+            let range = 1u64 << (level * LEVEL_BITS);
+            deadlines.extend([range - 1, range, range + 1, 3 * range + 5]);
+        }
+        deadlines.push(MAX_DURATION);
+        deadlines
+    }
+
+    #[test]
+    fn fires_at_deadline_across_levels() {
+        let fired = Fired::default();
+        let mut wheel = Wheel::new();
+        let deadlines = boundaries();
+        for (id, &when) in deadlines.iter().enumerate() {
+            wheel.insert(once(id as u64, when, &fired));
+        }
+        for (id, &when) in deadlines.iter().enumerate() {
+            wheel.advance(when - 1);
+            assert_eq!(take(&fired), Vec::<u64>::new(), "early at {}", when - 1);
+            wheel.advance(when);
+            assert_eq!(take(&fired), vec![id as u64], "deadline {when}");
+        }
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn fires_everything_due_when_jumping_ahead() {
+        let fired = Fired::default();
+        let mut wheel = Wheel::new();
+        let deadlines = boundaries();
+        for (id, &when) in deadlines.iter().enumerate() {
+            wheel.insert(once(id as u64, when, &fired));
+        }
+        let now = 1 << (3 * LEVEL_BITS);
+        wheel.advance(now);
+        let mut fired_now = take(&fired);
+        fired_now.sort();
+        let due: Vec<u64> = (0..deadlines.len() as u64)
+            .filter(|&id| deadlines[id as usize] <= now)
+            .collect();
+        assert_eq!(fired_now, due);
+        wheel.advance(MAX_DURATION);
+        assert_eq!(take(&fired).len(), deadlines.len() - due.len());
+    }
+
+    #[test]
+    fn inserts_relative_to_elapsed() {
+        let fired = Fired::default();
+        let mut wheel = Wheel::new();
+        // not aligned to any slot
+        wheel.advance(4096 * 3 + 4000);
+        let base = wheel.elapsed;
+        let deadlines = [base + 1, base + 63, base + 64, base + 97, base + 4096, base + 262_145];
+        for (id, &when) in deadlines.iter().enumerate() {
+            wheel.insert(once(id as u64, when, &fired));
+        }
+        for (id, &when) in deadlines.iter().enumerate() {
+            wheel.advance(when - 1);
+            assert!(take(&fired).is_empty(), "early at {}", when - 1);
+            wheel.advance(when);
+            assert_eq!(take(&fired), vec![id as u64]);
+        }
+    }
+
+    #[test]
+    fn next_expiration_is_not_after_the_deadline() {
+        let fired = Fired::default();
+        for when in boundaries() {
+            let mut wheel = Wheel::new();
+            wheel.advance(when / 3);
+            wheel.insert(once(0, when, &fired));
+            let (_, _, next) = wheel.next_expiration().unwrap();
+            assert!(next > wheel.elapsed && next <= when, "{next} for {when}");
+        }
+        let mut wheel = Wheel::new();
+        wheel.insert(once(0, 10, &fired));
+        assert_eq!(wheel.next_expiration(), Some((0, 10, 10)));
+        assert!(Wheel::new().next_expiration().is_none());
+    }
+
+    #[test]
+    fn removed_entries_do_not_fire() {
+        let fired = Fired::default();
+        let mut wheel = Wheel::new();
+        wheel.insert(once(0, 70, &fired));
+        wheel.insert(once(1, 70, &fired));
+        wheel.insert(once(2, 5000, &fired));
+        wheel.remove(0);
+        wheel.remove(2);
+        wheel.advance(10_000);
+        assert_eq!(take(&fired), vec![1]);
+        assert!(wheel.is_empty());
+        assert!(wheel.next_expiration().is_none());
+    }
+
+    #[test]
+    fn intervals_reschedule() {
+        let ticks = Arc::new(AtomicU64::new(0));
+        let mut wheel = Wheel::new();
+        let counter = ticks.clone();
+        wheel.insert(Entry {
+            id: 0,
+            when: 100,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            kind: EntryKind::Interval {
+                period: 100,
+                tick: Box::new(move || counter.fetch_add(1, Ordering::Relaxed) < 2),
+            },
+        });
+        for now in [99, 100, 199, 200, 300, 400, 10_000] {
+            wheel.advance(now);
+        }
+        // the third tick returned `false`
+        assert_eq!(ticks.load(Ordering::Relaxed), 3);
+        assert!(wheel.is_empty());
+    }
+
+    #[test]
+    fn clamps_far_deadlines() {
+        let fired = Fired::default();
+        let mut wheel = Wheel::new();
+        wheel.advance(1000);
+        wheel.insert(once(0, u64::MAX, &fired));
+        wheel.advance(1000 + MAX_DURATION - 1);
+        assert!(take(&fired).is_empty());
+        wheel.advance(1000 + MAX_DURATION);
+        assert_eq!(take(&fired), vec![0]);
+    }
+
+    #[test]
+    fn many_timers() {
+        let fired = Fired::default();
+        let mut wheel = Wheel::new();
+        // spread over the first three levels, with a step that hits every slot
+        let deadlines: Vec<u64> = (1..3072u64).map(|i| i * 97 % 300_000 + 1).collect();
+        for (id, &when) in deadlines.iter().enumerate() {
+            wheel.insert(once(id as u64, when, &fired));
+        }
+        let mut now = 0;
+        while !wheel.is_empty() {
+            now += 13;
+            wheel.advance(now);
+            for id in take(&fired) {
+                let when = deadlines[id as usize];
+                assert!(when <= now && when + 13 > now, "{when} fired at {now}");
+            }
+        }
+    }
+}
@@ -1,17 +1,72 @@
 use proc_macro::TokenStream;
 
 use quote::ToTokens;
-use syn::parse_macro_input;
+use syn::{parse_macro_input, DeriveInput};
 
-use crate::msg_set::MessageSetImpl;
+use crate::msg_service::{MsgService, MsgServiceArgs};
+use crate::msg_set::{MessageSetImpl, MsgSetArgs};
 
+mod message;
+mod msg_service;
 mod msg_set;
 
+/// Generates the variant enums and impls of a `MessageSet`.
+///
+/// The variant of a message is named after the last segment of its path. Messages with the same
+/// name are named with `#[variant(path::Ping = OtherPing)]` on the category listing them.
+/// `#[msg_set(client)]` also generates a `{Set}Client` with a method per message, `r#move` for a
+/// message named `Move`, and `#[msg_set(crate = path)]` names msg_channel when it is re-exported
+/// by another crate.
+///
+/// The generated types are declared next to the set and are `pub` by default. The attribute is on
+/// the impl, so it can't see how the set struct is declared: a set that is not `pub`, or that has
+/// messages that are not `pub`, passes its visibility with `#[msg_set(vis = pub(crate))]`, or an
+/// empty `vis =` for private. Without it, each such message warns with `private_interfaces`, and
+/// a `vis` narrower than the set fails with E0446. `#[msg_service]` declares the set itself and
+/// uses the visibility of its trait. The names start with the name of the set, or
+/// `#[msg_set(name = Kv)]` for `KvSyncVariant`, `KvClient` and so on.
+///
+/// `#[msg_set(derive(Debug, Clone, serde::Serialize))]` derives the traits on the variant enums of
+/// the messages, which `MessageSetItem` then implements as well. The replays are often not like
+/// their messages, e.g. a `ReplyStream` is not `Debug`, so the replay enums and
+/// `MessageSetReplayItem` derive the traits of `reply_derive(Debug)` instead. Sets with
+/// `type Messages` derive the serde traits with the `serde` feature of msg_channel.
 #[proc_macro_attribute]
-pub fn msg_set(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let actor = parse_macro_input!(item as MessageSetImpl);
+pub fn msg_set(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as MsgSetArgs);
+    let actor = parse_macro_input!(item as MessageSetImpl).with_args(args);
     {
         // let a = 1;
     }
     TokenStream::from(actor.into_token_stream())
 }
+
+/// Generates a message per method of a trait, the handle impls for `handler`, a `{Trait}MsgSet`
+/// and a typed `{Trait}Client`.
+///
+/// The category of each message follows from its method: `&mut self` methods are `Sync` or
+/// `Async`, `&self` methods `SyncConcurrent` or `AsyncConcurrent`. Like with `#[msg_set]`,
+/// `crate = path` names a re-exported msg_channel.
+#[proc_macro_attribute]
+pub fn msg_service(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as MsgServiceArgs);
+    let service = parse_macro_input!(item as MsgService);
+    TokenStream::from(service.expand(&args))
+}
+
+/// Implements `Message` from `#[message(reply = Type, mode = ...)]`.
+///
+/// The mode is one of `async`, `sync`, `async_concurrent` and `sync_concurrent`, the reply
+/// defaults to `()`.
+#[proc_macro_derive(Message, attributes(message))]
+pub fn derive_message(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    message::derive_message(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn default_crate_path() -> syn::Path {
+    syn::parse_quote!(::msg_channel)
+}
+
@@ -0,0 +1,80 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{DeriveInput, Ident, Token, Type};
+
+/// The arguments of `#[message(...)]`.
+struct MessageArgs {
+    reply: Option<Type>,
+    mode: Option<Ident>,
+    krate: Option<syn::Path>,
+}
+
+impl Parse for MessageArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = MessageArgs {
+            reply: None,
+            mode: None,
+            krate: None,
+        };
+        while !input.is_empty() {
+            let key = input.call(Ident::parse_any)?;
+            input.parse::<Token![=]>()?;
+            match &*key.to_string() {
+                "reply" => args.reply = Some(input.parse()?),
+                "crate" => args.krate = Some(input.parse()?),
+                "mode" => {
+                    let mode = input.call(Ident::parse_any)?;
+                    let name = match &*mode.to_string() {
+                        "async" => "Async",
+                        "sync" => "Sync",
+                        "async_concurrent" => "AsyncConcurrent",
+                        "sync_concurrent" => "SyncConcurrent",
+                        _ => {
+                            return Err(syn::Error::new(
+                                mode.span(),
+                                "expected `async`, `sync`, `async_concurrent` or `sync_concurrent`",
+                            ))
+                        }
+                    };
+                    args.mode = Some(Ident::new(name, mode.span()));
+                }
+                _ => return Err(syn::Error::new(key.span(), "unknown message argument")),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
+pub fn derive_message(input: DeriveInput) -> syn::Result<TokenStream> {
+    let mut reply = None;
+    let mut mode = None;
+    let mut krate = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("message")) {
+        let args: MessageArgs = attr.parse_args()?;
+        reply = args.reply.or(reply);
+        mode = args.mode.or(mode);
+        krate = args.krate.or(krate);
+    }
+    let krate = krate.unwrap_or_else(crate::default_crate_path);
+    let reply = reply.unwrap_or_else(|| syn::parse_quote!(()));
+    let Some(mode) = mode else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "missing `#[message(mode = ...)]`",
+        ));
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #krate::Message for #ident #ty_generics #where_clause {
+            type Reply = #reply;
+            type Mode = #krate::mode::#mode;
+        }
+    })
+}
@@ -0,0 +1,263 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{FnArg, ItemTrait, Pat, ReturnType, Token, TraitItem, Type};
+
+/// `#[msg_service(handler = Type)]`, optionally with `crate = path`
+pub struct MsgServiceArgs {
+    handler: Type,
+    krate: syn::Path,
+}
+
+impl Parse for MsgServiceArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut handler = None;
+        let mut krate = crate::default_crate_path();
+        while !input.is_empty() {
+            let key = input.call(Ident::parse_any)?;
+            input.parse::<Token![=]>()?;
+            match &*key.to_string() {
+                "handler" => handler = Some(input.parse()?),
+                "crate" => krate = input.parse()?,
+                _ => return Err(syn::Error::new(key.span(), "expected `handler = Type`")),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        let Some(handler) = handler else {
+            return Err(input.error("expected `handler = Type`"));
+        };
+        Ok(MsgServiceArgs { handler, krate })
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Category {
+    Async,
+    Sync,
+    AsyncConcurrent,
+    SyncConcurrent,
+}
+
+struct Method {
+    ident: Ident,
+    msg: Ident,
+    params: Vec<(Ident, Type)>,
+    replay: Type,
+    category: Category,
+}
+
+pub struct MsgService {
+    item_trait: ItemTrait,
+    methods: Vec<Method>,
+}
+
+impl Parse for MsgService {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let item_trait: ItemTrait = input.parse()?;
+        if !item_trait.generics.params.is_empty() {
+            return Err(syn::Error::new(
+                item_trait.generics.span(),
+                "msg_service traits can't be generic",
+            ));
+        }
+        let mut methods = vec![];
+        for item in item_trait.items.iter() {
+            let TraitItem::Fn(item_fn) = item else {
+                continue;
+            };
+            let sig = &item_fn.sig;
+            if !sig.generics.params.is_empty() {
+                return Err(syn::Error::new(
+                    sig.generics.span(),
+                    "msg_service methods can't be generic",
+                ));
+            }
+            let mut inputs = sig.inputs.iter();
+            let mutable = match inputs.next() {
+                Some(FnArg::Receiver(receiver)) if receiver.reference.is_some() => {
+                    receiver.mutability.is_some()
+                }
+                _ => {
+                    return Err(syn::Error::new(
+                        sig.span(),
+                        "msg_service methods take `&self` or `&mut self`",
+                    ))
+                }
+            };
+            let params = inputs
+                .map(|input| match input {
+                    FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                        Pat::Ident(pat_ident) => {
+                            Ok((pat_ident.ident.clone(), pat_type.ty.as_ref().clone()))
+                        }
+                        pat => Err(syn::Error::new(pat.span(), "expected a parameter name")),
+                    },
+                    FnArg::Receiver(receiver) => {
+                        Err(syn::Error::new(receiver.span(), "unexpected receiver"))
+                    }
+                })
+                .collect::<syn::Result<_>>()?;
+            let replay = match &sig.output {
+                ReturnType::Default => syn::parse_quote!(()),
+                ReturnType::Type(_, ty) => ty.as_ref().clone(),
+            };
+            let category = match (sig.asyncness.is_some(), mutable) {
+                (true, true) => Category::Async,
+                (false, true) => Category::Sync,
+                (true, false) => Category::AsyncConcurrent,
+                (false, false) => Category::SyncConcurrent,
+            };
+            methods.push(Method {
+                ident: sig.ident.clone(),
+                msg: format_ident!("{}{}", item_trait.ident, upper_camel_case(&sig.ident)),
+                params,
+                replay,
+                category,
+            });
+        }
+        Ok(MsgService {
+            item_trait,
+            methods,
+        })
+    }
+}
+
+fn upper_camel_case(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+impl MsgService {
+    pub fn expand(&self, args: &MsgServiceArgs) -> TokenStream {
+        let item_trait = &self.item_trait;
+        let vis = &item_trait.vis;
+        let trait_ident = &item_trait.ident;
+        let handler = &args.handler;
+        let krate = &args.krate;
+        let set = format_ident!("{}MsgSet", trait_ident);
+        let client = format_ident!("{}Client", trait_ident);
+
+        // the trait is called through the generated handle impls, where the futures of its
+        // `async fn`s don't need to be `Send`
+        let mut tokens = quote! {
+            #[allow(async_fn_in_trait)]
+            #item_trait
+        };
+        let mut categories: [Vec<&Ident>; 4] = Default::default();
+        for method in self.methods.iter() {
+            let Method {
+                ident,
+                msg,
+                params,
+                replay,
+                category,
+            } = method;
+            let names: Vec<_> = params.iter().map(|(name, _)| name).collect();
+            let types: Vec<_> = params.iter().map(|(_, ty)| ty).collect();
+            let handle_impl = match category {
+                Category::Async => quote! {
+                    impl #krate::HandleAsync<#msg> for #handler {
+                        type Replay = #replay;
+
+                        async fn handle(&mut self, msg: #msg) -> Self::Replay {
+                            <Self as #trait_ident>::#ident(self, #(msg.#names),*).await
+                        }
+                    }
+                },
+                Category::Sync => quote! {
+                    impl #krate::HandleSync<#msg> for #handler {
+                        type Replay = #replay;
+
+                        fn handle(&mut self, msg: #msg) -> Self::Replay {
+                            <Self as #trait_ident>::#ident(self, #(msg.#names),*)
+                        }
+                    }
+                },
+                Category::AsyncConcurrent => quote! {
+                    impl #krate::HandleAsyncConcurrent<#msg> for #handler {
+                        type Replay = #replay;
+
+                        async fn handle(&self, msg: #msg) -> Self::Replay {
+                            <Self as #trait_ident>::#ident(self, #(msg.#names),*).await
+                        }
+                    }
+                },
+                Category::SyncConcurrent => quote! {
+                    impl #krate::HandleSyncConcurrent<#msg> for #handler {
+                        type Replay = #replay;
+
+                        fn handle(&self, msg: #msg) -> Self::Replay {
+                            <Self as #trait_ident>::#ident(self, #(msg.#names),*)
+                        }
+                    }
+                },
+            };
+            categories[*category as usize].push(msg);
+            tokens.extend(quote! {
+                #vis struct #msg {
+                    #(pub #names: #types,)*
+                }
+
+                #handle_impl
+            });
+        }
+
+        let [async_msgs, sync_msgs, async_concurrent_msgs, sync_concurrent_msgs] = &categories;
+        let client_methods = self.methods.iter().map(|method| {
+            let Method {
+                ident,
+                msg,
+                params,
+                replay,
+                ..
+            } = method;
+            let names: Vec<_> = params.iter().map(|(name, _)| name).collect();
+            let types: Vec<_> = params.iter().map(|(_, ty)| ty).collect();
+            quote! {
+                pub async fn #ident(&self, #(#names: #types),*) -> ::core::result::Result<#replay, #krate::MsgSendError<#set>> {
+                    ::core::result::Result::Ok(self.sender.send(#msg { #(#names),* })?.await)
+                }
+            }
+        });
+        tokens.extend(quote! {
+            #vis struct #set;
+
+            #[#krate::msg_set(crate = #krate, vis = #vis)]
+            impl #krate::MessageSet for #set {
+                type Handler = #handler;
+                type Async = (#(#async_msgs,)*);
+                type Sync = (#(#sync_msgs,)*);
+                type AsyncConcurrent = (#(#async_concurrent_msgs,)*);
+                type SyncConcurrent = (#(#sync_concurrent_msgs,)*);
+            }
+
+            #[derive(::core::clone::Clone)]
+            #vis struct #client {
+                pub sender: #krate::MessageSetSender<#set>,
+            }
+
+            impl #client {
+                pub fn new(sender: #krate::MessageSetSender<#set>) -> Self {
+                    Self { sender }
+                }
+
+                #(#client_methods)*
+            }
+        });
+        tokens
+    }
+}
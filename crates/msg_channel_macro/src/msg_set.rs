@@ -1,14 +1,133 @@
-
 use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote, ToTokens};
-use syn::{ImplItem, ItemImpl, Token, Type};
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
+use syn::{Generics, ImplItem, ItemImpl, Token, Type, Visibility};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 
+/// The arguments of `#[msg_set(...)]`.
+pub struct MsgSetArgs {
+    /// Generates a `{Set}Client` with a method per message.
+    client: bool,
+    /// The path generated code refers to msg_channel by, `crate = path`.
+    krate: syn::Path,
+    /// The visibility of the generated types, `vis = pub(crate)`. `pub` by default, since the
+    /// attribute can't see the visibility of the set struct.
+    vis: Visibility,
+    /// Replaces the set name in the names of the generated types, `name = Kv`.
+    name: Option<Ident>,
+    /// Derived by the variant enums of the messages, `derive(Debug, Clone)`.
+    derives: Vec<syn::Path>,
+    /// Derived by the variant enums of the replays, `reply_derive(Debug)`. Separate, since
+    /// replays like `ReplyStream` implement less than their messages.
+    reply_derives: Vec<syn::Path>,
+}
+
+impl Default for MsgSetArgs {
+    fn default() -> Self {
+        Self {
+            client: false,
+            krate: crate::default_crate_path(),
+            vis: syn::parse_quote!(pub),
+            name: None,
+            derives: vec![],
+            reply_derives: vec![],
+        }
+    }
+}
+
+impl Parse for MsgSetArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = MsgSetArgs::default();
+        while !input.is_empty() {
+            let key = input.call(Ident::parse_any)?;
+            match &*key.to_string() {
+                "client" => args.client = true,
+                "crate" => {
+                    input.parse::<Token![=]>()?;
+                    args.krate = input.parse()?;
+                }
+                "vis" => {
+                    input.parse::<Token![=]>()?;
+                    args.vis = input.parse()?;
+                }
+                "name" => {
+                    input.parse::<Token![=]>()?;
+                    args.name = Some(input.parse()?);
+                }
+                "derive" | "reply_derive" => {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let derives = Punctuated::<syn::Path, Token![,]>::parse_terminated(&content)?;
+                    if key == "derive" {
+                        args.derives.extend(derives);
+                    } else {
+                        args.reply_derives.extend(derives);
+                    }
+                }
+                _ => return Err(syn::Error::new(key.span(), "unknown msg_set argument")),
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
 pub struct MessageSetImpl {
     item_impl: ItemImpl,
     ident: Ident,
+    args: MsgSetArgs,
+}
+
+impl MessageSetImpl {
+    pub fn with_args(self, args: MsgSetArgs) -> Self {
+        Self { args, ..self }
+    }
+}
+
+// `MsgA` -> `msg_a`, `HTTPRequest` -> `http_request`
+fn snake_case(ident: &Ident) -> Ident {
+    let chars: Vec<char> = ident.to_string().chars().collect();
+    let mut snake = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let after_lower = i > 0 && !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let before_lower = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_lower || before_lower {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(*c);
+        }
+    }
+    format_ident!("{}", snake, span = ident.span())
+}
+
+// the client method of a message, `Move` -> `r#move`
+fn method_name(msg: &Msg) -> syn::Result<Ident> {
+    let method = snake_case(&msg.name);
+    if syn::parse2::<Ident>(method.to_token_stream()).is_ok() {
+        return Ok(method);
+    }
+    match &*method.to_string() {
+        // keywords that can't be raw identifiers
+        "crate" | "self" | "super" => Err(syn::Error::new(
+            msg.name.span(),
+            format!(
+                "the client method of `{}` would be `{}`, name this message with `#[variant({} = OtherName)]`",
+                type_string(&msg.ty),
+                method,
+                type_string(&msg.ty),
+            ),
+        )),
+        keyword => Ok(Ident::new_raw(keyword, method.span())),
+    }
 }
 
 impl Parse for MessageSetImpl {
@@ -32,94 +151,726 @@ impl Parse for MessageSetImpl {
             }
         };
 
-        Ok(MessageSetImpl { item_impl, ident })
+        Ok(MessageSetImpl {
+            item_impl,
+            ident,
+            args: MsgSetArgs::default(),
+        })
     }
 }
 impl ToTokens for MessageSetImpl {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let item_impl = &self.item_impl;
+        let mut item_impl = self.item_impl.clone();
 
         let mut handler_ident: Option<Type> = None;
-        let mut async_msg_idents: Punctuated<Type, Token![,]> = Punctuated::default();
-        let mut sync_msg_idents: Punctuated<Type, Token![,]> = Punctuated::default();
-        let mut async_concurrent_msg_idents: Punctuated<Type, Token![,]> = Punctuated::default();
-        let mut sync_concurrent_msg_idents: Punctuated<Type, Token![,]> = Punctuated::default();
-
-        for item in item_impl.items.iter() {
-            match item {
-                ImplItem::Type(item_type) => {
-                    let item_type_name = item_type.ident.to_string();
-                    match &*item_type_name {
-                        "Handler" => {
-                            handler_ident = Some(item_type.ty.clone());
-                        }
-                        "Async" => {
-                            let Type::Tuple(tuple) = &item_type.ty else {
-                                panic!("Async type must is tuple");
-                            };
-                            // tuple.elems.iter().map(|n| {
-                            //     match n {
-                            //         // Type::ImplTrait(_) => {}
-                            //         // Type::TraitObject(_) => {}
-                            //         // Type::Tuple(_) => {}
-                            //         Type::Path(n) => {
-                            //             n.path
-                            //         }
-                            //         _ => {
-                            //             panic!("{n:?} msg type is supported");
-                            //         }
-                            //     }
-                            // })
-                            async_msg_idents = tuple.elems.clone();
-                        }
-                        "Sync" => {
-                            let Type::Tuple(tuple) = &item_type.ty else {
-                                panic!("Async type must is tuple");
-                            };
-                            sync_msg_idents = tuple.elems.clone();
-                        }
-                        "AsyncConcurrent" => {
-                            let Type::Tuple(tuple) = &item_type.ty else {
-                                panic!("Async type must is tuple");
-                            };
-                            async_concurrent_msg_idents = tuple.elems.clone();
-                        }
-                        "SyncConcurrent" => {
-                            let Type::Tuple(tuple) = &item_type.ty else {
-                                panic!("Async type must is tuple");
-                            };
-                            sync_concurrent_msg_idents = tuple.elems.clone();
-                        }
-                        _ => continue,
-                    }
+        let mut categories: [Vec<Type>; 4] = Default::default();
+        let mut flat_msgs: Option<Vec<Type>> = None;
+
+        let mut renames: Vec<VariantName> = Vec::new();
+        let mut errors: Vec<syn::Error> = Vec::new();
+
+        for item in item_impl.items.iter_mut() {
+            let ImplItem::Type(item_type) = item else {
+                continue;
+            };
+            // `#[variant(...)]` is ours, the rest stays on the item
+            item_type.attrs.retain(|attr| {
+                if !attr.path().is_ident("variant") {
+                    return true;
+                }
+                match attr.parse_args_with(Punctuated::<VariantName, Token![,]>::parse_terminated) {
+                    Ok(names) => renames.extend(names),
+                    Err(err) => errors.push(err),
+                }
+                false
+            });
+            let item_type_name = item_type.ident.to_string();
+            if item_type_name == "Handler" {
+                handler_ident = Some(item_type.ty.clone());
+            } else if item_type_name == "Messages" {
+                flat_msgs = Some(msg_types(&item_type.ty));
+            } else if let Some(i) = CATEGORIES
+                .iter()
+                .position(|category| category.prefix == item_type_name)
+            {
+                categories[i] = msg_types(&item_type.ty);
+            }
+        }
+
+        let has_event = item_impl
+            .items
+            .iter()
+            .any(|item| matches!(item, ImplItem::Type(item_type) if item_type.ident == "Event"));
+        if !has_event {
+            item_impl.items.push(syn::parse_quote!(type Event = ();));
+        }
+
+        if flat_msgs.is_some() {
+            // `Messages` isn't an item of `MessageSet`, its messages are sorted by their mode
+            item_impl.items.retain(
+                |item| !matches!(item, ImplItem::Type(item_type) if item_type.ident == "Messages"),
+            );
+            for category in CATEGORIES.iter() {
+                let declared = item_impl.items.iter().any(
+                    |item| matches!(item, ImplItem::Type(item_type) if item_type.ident == category.prefix),
+                );
+                if !declared {
+                    let category = format_ident!("{}", category.prefix);
+                    item_impl.items.push(syn::parse_quote!(type #category = ();));
                 }
-                _ => continue,
             }
         }
 
-        let handler_ident = handler_ident.unwrap();
-        let ident = &self.ident;
+        let Some(handler) = handler_ident else {
+            tokens.extend(
+                syn::Error::new(
+                    self.item_impl.impl_token.span,
+                    "missing `type Handler = ...;`, the type handling the messages of the set",
+                )
+                .to_compile_error(),
+            );
+            return;
+        };
+        let ident = self.args.name.as_ref().unwrap_or(&self.ident);
+        let krate = &self.args.krate;
+        let vis = &self.args.vis;
+        let set = SetGen {
+            krate,
+            ident,
+            self_ty: &item_impl.self_ty,
+            handler: &handler,
+            vis,
+            generics: &item_impl.generics,
+            derives: &self.args.derives,
+            reply_derives: &self.args.reply_derives,
+        };
+        let self_ty = set.self_ty;
+        let (impl_generics, ty_generics, where_clause) = set.generics.split_for_impl();
+
+        let mut msgs = |msgs: &Vec<Type>| -> Vec<Msg> {
+            msgs.iter()
+                .filter_map(|ty| match variant_name(ty, &renames) {
+                    Ok(name) => Some(Msg { ty: ty.clone(), name }),
+                    Err(err) => {
+                        errors.push(err);
+                        None
+                    }
+                })
+                .collect()
+        };
+        let flat_msgs = flat_msgs.as_ref().map(&mut msgs);
+        let categories = categories.each_ref().map(msgs);
+        let all_msgs: Vec<(&str, &Msg)> = flat_msgs
+            .iter()
+            .flatten()
+            .map(|msg| ("Messages", msg))
+            .chain(
+                CATEGORIES
+                    .iter()
+                    .zip(&categories)
+                    .flat_map(|(category, msgs)| msgs.iter().map(|msg| (category.prefix, msg))),
+            )
+            .collect();
+        for (i, (listed_in, msg)) in all_msgs.iter().enumerate() {
+            let previous = &all_msgs[..i];
+            if let Some((other_listed_in, _)) = previous.iter().find(|(_, other)| other.ty == msg.ty) {
+                let message = if listed_in == other_listed_in {
+                    format!("`{}` is listed twice in `{}`", type_string(&msg.ty), listed_in)
+                } else {
+                    format!(
+                        "`{}` is listed in both `{}` and `{}`, a message belongs to a single category",
+                        type_string(&msg.ty),
+                        other_listed_in,
+                        listed_in,
+                    )
+                };
+                errors.push(syn::Error::new_spanned(&msg.ty, message));
+            } else if previous.iter().any(|(_, other)| other.name == msg.name) {
+                errors.push(syn::Error::new_spanned(
+                    &msg.ty,
+                    format!(
+                        "the variant name `{}` is already used, name this message with `#[variant({} = OtherName)]`",
+                        msg.name,
+                        type_string(&msg.ty),
+                    ),
+                ));
+            }
+        }
+        if let Some(msg) = flat_msgs.as_ref().and(categories.iter().flatten().next()) {
+            errors.push(syn::Error::new_spanned(
+                &msg.ty,
+                "a set with `type Messages` takes the categories from the message modes",
+            ));
+        }
+        let methods: Vec<Ident> = if self.args.client {
+            all_msgs
+                .iter()
+                .filter_map(|(_, msg)| method_name(msg).map_err(|err| errors.push(err)).ok())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        if !errors.is_empty() {
+            tokens.extend(errors.iter().map(syn::Error::to_compile_error));
+            return;
+        }
+        let all_msgs: Vec<&Msg> = all_msgs.into_iter().map(|(_, msg)| msg).collect();
+
+        let variants = match &flat_msgs {
+            Some(flat_msgs) => set.flat_variants(flat_msgs),
+            None => CATEGORIES
+                .iter()
+                .zip(&categories)
+                .map(|(category, msgs)| set.category_variants(category, msgs))
+                .collect(),
+        };
+        let all_msgs: Vec<&Type> = all_msgs.iter().map(|msg| &msg.ty).collect();
+
         let actor_info_impl = {
             let async_msg = format_ident!("{}AsyncVariant", ident);
             let sync_msg = format_ident!("{}SyncVariant", ident);
             let async_concurrent_msg = format_ident!("{}AsyncConcurrentVariant", ident);
             let sync_concurrent_msg = format_ident!("{}SyncConcurrentVariant", ident);
             quote! {
-                impl MessageVariantSet for #ident {
-                    type AsyncVariant = #async_msg;
-                    type SyncVariant = #sync_msg;
-                    type AsyncConcurrentVariant = #async_concurrent_msg;
-                    type SyncConcurrentVariant = #sync_concurrent_msg;
+                impl #impl_generics #krate::MessageVariantSet for #self_ty #where_clause {
+                    type AsyncVariant = #async_msg #ty_generics;
+                    type SyncVariant = #sync_msg #ty_generics;
+                    type AsyncConcurrentVariant = #async_concurrent_msg #ty_generics;
+                    type SyncConcurrentVariant = #sync_concurrent_msg #ty_generics;
+                }
+            }
+        };
+        let any_impl = quote! {
+            impl #impl_generics #krate::MessageSetAny for #self_ty #where_clause {
+                #[allow(unused_variables)]
+                fn downcast_item(
+                    msg: #krate::AnyMessage,
+                    reply_to: #krate::ReplyTo<#krate::AnyMessage>,
+                ) -> ::core::result::Result<#krate::MsgAndReplaySender<Self>, #krate::AnyMessage> {
+                    #(
+                        let msg = match msg.downcast::<#all_msgs>() {
+                            ::core::result::Result::Ok(msg) => {
+                                let reply_to = reply_to.map(|replay| {
+                                    ::std::boxed::Box::new(<Self as #krate::MessageSetContains<#all_msgs>>::from_replay_item(replay)) as #krate::AnyMessage
+                                });
+                                return ::core::result::Result::Ok((<Self as #krate::MessageSetContains<#all_msgs>>::into_item(*msg), reply_to));
+                            }
+                            ::core::result::Result::Err(msg) => msg,
+                        };
+                    )*
+                    ::core::result::Result::Err(msg)
                 }
             }
         };
         tokens.extend(quote! {
             #item_impl
-            msg_channel::internal::impl_msg_handle_for_variant!(#ident;#handler_ident;Async;HandleAsync;#async_msg_idents);
-            msg_channel::internal::impl_sync_msg_handle_for_variant!(#ident;#handler_ident;Sync;HandleSync;#sync_msg_idents);
-            msg_channel::internal::impl_concurrent_msg_handle_for_variant!(#ident;#handler_ident;AsyncConcurrent;HandleAsyncConcurrent;#async_concurrent_msg_idents);
-            msg_channel::internal::impl_sync_concurrent_msg_handle_for_variant!(#ident;#handler_ident;SyncConcurrent;HandleSyncConcurrent;#sync_concurrent_msg_idents);
+            #variants
             #actor_info_impl
+            #any_impl
+        });
+
+        if self.args.client {
+            let client = format_ident!("{}Client", ident);
+            let generics = set.generics;
+            let methods = all_msgs.iter().zip(&methods).map(|(msg, method)| {
+                quote! {
+                    pub fn #method(
+                        &self,
+                        msg: #msg,
+                    ) -> ::core::result::Result<#krate::ReplyFuture<<#self_ty as #krate::MessageSetContains<#msg>>::Replay>, #krate::MsgSendError<#self_ty>> {
+                        self.sender.send(msg)
+                    }
+                }
+            });
+            tokens.extend(quote! {
+                #vis struct #client #generics #where_clause {
+                    pub sender: #krate::MessageSetSender<#self_ty>,
+                }
+
+                impl #impl_generics ::core::clone::Clone for #client #ty_generics #where_clause {
+                    fn clone(&self) -> Self {
+                        Self::new(self.sender.clone())
+                    }
+                }
+
+                impl #impl_generics #client #ty_generics #where_clause {
+                    pub fn new(sender: #krate::MessageSetSender<#self_ty>) -> Self {
+                        Self { sender }
+                    }
+
+                    #(#methods)*
+                }
+
+                impl #impl_generics ::core::convert::From<#krate::MessageSetSender<#self_ty>> for #client #ty_generics #where_clause {
+                    fn from(sender: #krate::MessageSetSender<#self_ty>) -> Self {
+                        Self::new(sender)
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// A category of `MessageSet`, with the handler trait of its messages.
+struct Category {
+    prefix: &'static str,
+    handler_trait: &'static str,
+    slot_trait: &'static str,
+    is_async: bool,
+    is_concurrent: bool,
+}
+
+const CATEGORIES: [Category; 4] = [
+    Category {
+        prefix: "Async",
+        handler_trait: "HandleAsyncWithContext",
+        slot_trait: "AsyncSlot",
+        is_async: true,
+        is_concurrent: false,
+    },
+    Category {
+        prefix: "Sync",
+        handler_trait: "HandleSyncWithContext",
+        slot_trait: "SyncSlot",
+        is_async: false,
+        is_concurrent: false,
+    },
+    Category {
+        prefix: "AsyncConcurrent",
+        handler_trait: "HandleAsyncConcurrentWithContext",
+        slot_trait: "AsyncConcurrentSlot",
+        is_async: true,
+        is_concurrent: true,
+    },
+    Category {
+        prefix: "SyncConcurrent",
+        handler_trait: "HandleSyncConcurrentWithContext",
+        slot_trait: "SyncConcurrentSlot",
+        is_async: false,
+        is_concurrent: true,
+    },
+];
+
+// `(A, B)`, `()`, or a single `A`
+fn msg_types(ty: &Type) -> Vec<Type> {
+    match ty {
+        Type::Tuple(tuple) => tuple.elems.iter().cloned().collect(),
+        Type::Paren(paren) => vec![(*paren.elem).clone()],
+        ty => vec![ty.clone()],
+    }
+}
+
+/// A message of the set and the name of its variant.
+struct Msg {
+    ty: Type,
+    name: Ident,
+}
+
+/// `Type = Name` in `#[variant(...)]`, the explicit variant name of a message.
+struct VariantName {
+    ty: Type,
+    name: Ident,
+}
+
+impl Parse for VariantName {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let name = input.parse()?;
+        Ok(VariantName { ty, name })
+    }
+}
+
+// `crate :: msgs :: Ping < T >` -> `crate::msgs::Ping<T>`
+fn type_string(ty: &Type) -> String {
+    ty.to_token_stream()
+        .to_string()
+        .replace(" :: ", "::")
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+}
+
+// the explicit name, or the last segment: `crate::msgs::Ping` -> `Ping`, `Get<K>` -> `Get`
+fn variant_name(ty: &Type, renames: &[VariantName]) -> syn::Result<Ident> {
+    if let Some(rename) = renames.iter().find(|rename| rename.ty == *ty) {
+        return Ok(rename.name.clone());
+    }
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| syn::Error::new_spanned(ty, "expected a path to the type")),
+        _ => Err(syn::Error::new_spanned(
+            ty,
+            format!(
+                "can't derive a variant name, name this message with `#[variant({} = Name)]`",
+                type_string(ty),
+            ),
+        )),
+    }
+}
+
+/// What the items generated for a set have in common.
+struct SetGen<'a> {
+    krate: &'a syn::Path,
+    ident: &'a Ident,
+    self_ty: &'a Type,
+    handler: &'a Type,
+    /// The visibility of the generated types.
+    vis: &'a Visibility,
+    /// The generics of the impl, which every generated item takes as well.
+    generics: &'a Generics,
+    derives: &'a [syn::Path],
+    reply_derives: &'a [syn::Path],
+}
+
+/// How a variant of a category holds its message.
+enum Field<'a> {
+    /// The message itself, handled by the handler trait of the category.
+    Msg(&'a Category),
+    /// The slot of a message with a mode, see `type Messages`.
+    Slot(&'a Category),
+}
+
+impl SetGen<'_> {
+    /// The variant of generic enums that uses every generic parameter, it can't be constructed.
+    fn phantom_variant(&self) -> Option<TokenStream> {
+        let params: Vec<TokenStream> = self
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                syn::GenericParam::Type(param) => {
+                    let ident = &param.ident;
+                    Some(quote!(#ident))
+                }
+                syn::GenericParam::Lifetime(param) => {
+                    let lifetime = &param.lifetime;
+                    Some(quote!(&#lifetime ()))
+                }
+                syn::GenericParam::Const(_) => None,
+            })
+            .collect();
+        if params.is_empty() {
+            return None;
+        }
+        let krate = self.krate;
+        Some(quote! {
+            #[doc(hidden)]
+            __Phantom(#krate::internal::Inactive<(#(#params,)*)>)
+        })
+    }
+
+    fn field_ty(&self, field: &Field, ty: &Type) -> TokenStream {
+        let krate = self.krate;
+        match field {
+            Field::Msg(_) => quote!(#ty),
+            Field::Slot(category) => {
+                let prefix = format_ident!("{}", category.prefix);
+                quote!(#krate::internal::SlotOf<#ty, #krate::mode::#prefix>)
+            }
+        }
+    }
+
+    fn replay_ty(&self, field: &Field, ty: &Type) -> TokenStream {
+        let Self {
+            krate,
+            self_ty,
+            handler,
+            ..
+        } = self;
+        match field {
+            Field::Msg(category) => {
+                let handler_trait = format_ident!("{}", category.handler_trait);
+                quote!(<#handler as #krate::#handler_trait<#self_ty, #ty>>::Replay)
+            }
+            Field::Slot(_) => {
+                let slot = self.field_ty(field, ty);
+                quote!(<#slot as #krate::internal::ModeSlot>::Replay)
+            }
+        }
+    }
+
+    /// The variant enums of a category and the impls that dispatch them to the handler.
+    fn variants(
+        &self,
+        category: &Category,
+        field: Field,
+        msgs: &[Msg],
+    ) -> TokenStream {
+        let Self {
+            krate,
+            ident,
+            self_ty,
+            handler,
+            vis,
+            generics,
+            derives,
+            reply_derives,
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let prefix = format_ident!("{}", category.prefix);
+        let handler_trait = format_ident!("{}", category.handler_trait);
+        let variant = format_ident!("{}{}Variant", ident, prefix);
+        let replay_variant = format_ident!("{}{}ReplayVariant", ident, prefix);
+        let names: Vec<&Ident> = msgs.iter().map(|msg| &msg.name).collect();
+        let field_tys = msgs.iter().map(|msg| self.field_ty(&field, &msg.ty));
+        let replay_tys = msgs.iter().map(|msg| self.replay_ty(&field, &msg.ty));
+        let phantom = self.phantom_variant();
+        let phantom_arm = phantom.as_ref().map(|_| {
+            quote!(#variant::__Phantom(never) => never.unreachable(),)
         });
+        let phantom_ref_arm = phantom.as_ref().map(|_| {
+            quote!(#variant::__Phantom(ref never) => never.unreachable(),)
+        });
+
+        let (handle_calls, is_blocking_calls): (Vec<TokenStream>, Vec<TokenStream>) = msgs
+            .iter()
+            .map(|msg| {
+                let ty = &msg.ty;
+                match &field {
+                    Field::Msg(_) => (
+                        quote!(#krate::#handler_trait::<#self_ty, #ty>::handle(self, msg, cx)),
+                        quote!(#krate::#handler_trait::<#self_ty, #ty>::is_blocking(self, msg)),
+                    ),
+                    Field::Slot(category) => {
+                        let slot_trait = format_ident!("{}", category.slot_trait);
+                        (
+                            quote!(#krate::internal::#slot_trait::<#self_ty, #handler>::handle(msg, self, cx)),
+                            quote!(#krate::internal::#slot_trait::<#self_ty, #handler>::is_blocking(msg, self)),
+                        )
+                    }
+                }
+            })
+            .unzip();
+        let receiver = if category.is_concurrent {
+            quote!(&self)
+        } else {
+            quote!(&mut self)
+        };
+        let handle_fns = if category.is_async {
+            quote! {
+                async fn handle(
+                    #receiver,
+                    msg: #variant #ty_generics,
+                    cx: &mut #krate::Context<#self_ty, Self::Replay>,
+                ) -> ::core::option::Option<Self::Replay> {
+                    match msg {
+                        #(
+                        #variant::#names(msg) => {
+                            let cx = &mut cx.map_reply(#replay_variant::#names);
+                            if let ::core::option::Option::Some(replay) = #handle_calls.await {
+                                cx.reply(replay);
+                            }
+                            ::core::option::Option::None
+                        }
+                        )*
+                        #phantom_arm
+                    }
+                }
+            }
+        } else {
+            quote! {
+                fn is_blocking(&self, msg: &#variant #ty_generics) -> bool {
+                    match *msg {
+                        #(
+                        #variant::#names(ref msg) => #is_blocking_calls,
+                        )*
+                        #phantom_ref_arm
+                    }
+                }
+
+                fn handle(
+                    #receiver,
+                    msg: #variant #ty_generics,
+                    cx: &mut #krate::Context<#self_ty, Self::Replay>,
+                ) -> ::core::option::Option<Self::Replay> {
+                    match msg {
+                        #(
+                        #variant::#names(msg) => {
+                            let cx = &mut cx.map_reply(#replay_variant::#names);
+                            if let ::core::option::Option::Some(replay) = #handle_calls {
+                                cx.reply(replay);
+                            }
+                            ::core::option::Option::None
+                        }
+                        )*
+                        #phantom_arm
+                    }
+                }
+            }
+        };
+
+        let derive = (!derives.is_empty()).then(|| quote!(#[derive(#(#derives),*)]));
+        let reply_derive =
+            (!reply_derives.is_empty()).then(|| quote!(#[derive(#(#reply_derives),*)]));
+        quote! {
+            #derive
+            #vis enum #variant #generics #where_clause {
+                #(#names(#field_tys),)*
+                #phantom
+            }
+            #reply_derive
+            #vis enum #replay_variant #generics #where_clause {
+                #(#names(#replay_tys),)*
+                #phantom
+            }
+
+            impl #impl_generics #krate::MessageSetContains<#variant #ty_generics> for #self_ty #where_clause {
+                type Replay = #replay_variant #ty_generics;
+
+                fn into_item(msg: #variant #ty_generics) -> #krate::MessageSetItem<#self_ty> {
+                    #krate::MessageSetItem::<#self_ty>::#prefix(msg)
+                }
+
+                fn into_replay_item(replay: Self::Replay) -> #krate::MessageSetReplayItem<#self_ty> {
+                    #krate::MessageSetReplayItem::<#self_ty>::#prefix(replay)
+                }
+
+                fn from_replay_item(replay: #krate::MessageSetReplayItem<#self_ty>) -> Self::Replay {
+                    let #krate::MessageSetReplayItem::<#self_ty>::#prefix(replay) = replay else {
+                        ::core::unreachable!()
+                    };
+                    replay
+                }
+            }
+
+            impl #impl_generics #krate::#handler_trait<#self_ty, #variant #ty_generics> for #handler #where_clause {
+                type Replay = #replay_variant #ty_generics;
+
+                #handle_fns
+            }
+        }
+    }
+
+    /// The variants of a category listed in the set, with the replay types of the handler.
+    fn category_variants(&self, category: &Category, msgs: &[Msg]) -> TokenStream {
+        let Self {
+            krate,
+            ident,
+            self_ty,
+            handler,
+            generics,
+            ..
+        } = self;
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let prefix = format_ident!("{}", category.prefix);
+        let handler_trait = format_ident!("{}", category.handler_trait);
+        let variant = format_ident!("{}{}Variant", ident, prefix);
+        let replay_variant = format_ident!("{}{}ReplayVariant", ident, prefix);
+
+        let mut tokens = self.variants(category, Field::Msg(category), msgs);
+        for Msg { ty, name } in msgs {
+            let replay = quote!(<#handler as #krate::#handler_trait<#self_ty, #ty>>::Replay);
+            tokens.extend(quote! {
+                impl #impl_generics #krate::MessageSetContains<#ty> for #self_ty #where_clause {
+                    type Replay = #replay;
+
+                    fn into_item(msg: #ty) -> #krate::MessageSetItem<#self_ty> {
+                        #krate::MessageSetItem::<#self_ty>::#prefix(#variant::#name(msg))
+                    }
+
+                    fn into_replay_item(replay: Self::Replay) -> #krate::MessageSetReplayItem<#self_ty> {
+                        #krate::MessageSetReplayItem::<#self_ty>::#prefix(#replay_variant::#name(replay))
+                    }
+
+                    fn from_replay_item(replay: #krate::MessageSetReplayItem<#self_ty>) -> Self::Replay {
+                        let #krate::MessageSetReplayItem::<#self_ty>::#prefix(#replay_variant::#name(replay)) = replay else {
+                            ::core::unreachable!()
+                        };
+                        replay
+                    }
+                }
+            });
+        }
+        tokens
+    }
+
+    /// The variants of a set with `type Messages`.
+    ///
+    /// Every category lists every message, in a slot that only the category of its mode can hold.
+    fn flat_variants(&self, msgs: &[Msg]) -> TokenStream {
+        let Self {
+            krate,
+            ident,
+            self_ty,
+            generics,
+            ..
+        } = self;
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        let mut tokens = TokenStream::new();
+        for category in CATEGORIES.iter() {
+            tokens.extend(self.variants(category, Field::Slot(category), msgs));
+        }
+
+        let prefixes: Vec<Ident> = CATEGORIES
+            .iter()
+            .map(|category| format_ident!("{}", category.prefix))
+            .collect();
+        let variants: Vec<Ident> = prefixes
+            .iter()
+            .map(|prefix| format_ident!("{}{}Variant", ident, prefix))
+            .collect();
+        let replay_variants: Vec<Ident> = prefixes
+            .iter()
+            .map(|prefix| format_ident!("{}{}ReplayVariant", ident, prefix))
+            .collect();
+        let (last_prefix, prefixes) = prefixes.split_last().unwrap();
+        let (last_variant, variants) = variants.split_last().unwrap();
+        let (last_replay_variant, replay_variants) = replay_variants.split_last().unwrap();
+        for Msg { ty, name } in msgs {
+            tokens.extend(quote! {
+                impl #impl_generics #krate::MessageSetContains<#ty> for #self_ty #where_clause {
+                    type Replay = <#ty as #krate::Message>::Reply;
+
+                    fn into_item(msg: #ty) -> #krate::MessageSetItem<#self_ty> {
+                        #(
+                        let msg = match <#krate::internal::SlotOf<#ty, #krate::mode::#prefixes> as #krate::internal::ModeSlot>::try_from_msg(msg) {
+                            ::core::result::Result::Ok(slot) => return #krate::MessageSetItem::<#self_ty>::#prefixes(#variants::#name(slot)),
+                            ::core::result::Result::Err(msg) => msg,
+                        };
+                        )*
+                        match <#krate::internal::SlotOf<#ty, #krate::mode::#last_prefix> as #krate::internal::ModeSlot>::try_from_msg(msg) {
+                            ::core::result::Result::Ok(slot) => #krate::MessageSetItem::<#self_ty>::#last_prefix(#last_variant::#name(slot)),
+                            ::core::result::Result::Err(_) => ::core::unreachable!(),
+                        }
+                    }
+
+                    fn into_replay_item(replay: Self::Replay) -> #krate::MessageSetReplayItem<#self_ty> {
+                        #(
+                        let replay = match <#krate::internal::SlotOf<#ty, #krate::mode::#prefixes> as #krate::internal::ModeSlot>::try_from_reply(replay) {
+                            ::core::result::Result::Ok(replay) => return #krate::MessageSetReplayItem::<#self_ty>::#prefixes(#replay_variants::#name(replay)),
+                            ::core::result::Result::Err(replay) => replay,
+                        };
+                        )*
+                        match <#krate::internal::SlotOf<#ty, #krate::mode::#last_prefix> as #krate::internal::ModeSlot>::try_from_reply(replay) {
+                            ::core::result::Result::Ok(replay) => #krate::MessageSetReplayItem::<#self_ty>::#last_prefix(#last_replay_variant::#name(replay)),
+                            ::core::result::Result::Err(_) => ::core::unreachable!(),
+                        }
+                    }
+
+                    #[allow(unreachable_patterns)]
+                    fn from_replay_item(replay: #krate::MessageSetReplayItem<#self_ty>) -> Self::Replay {
+                        match replay {
+                            #(
+                            #krate::MessageSetReplayItem::<#self_ty>::#prefixes(#replay_variants::#name(replay)) => {
+                                <#krate::internal::SlotOf<#ty, #krate::mode::#prefixes> as #krate::internal::ModeSlot>::into_reply(replay)
+                            }
+                            )*
+                            #krate::MessageSetReplayItem::<#self_ty>::#last_prefix(#last_replay_variant::#name(replay)) => {
+                                <#krate::internal::SlotOf<#ty, #krate::mode::#last_prefix> as #krate::internal::ModeSlot>::into_reply(replay)
+                            }
+                            _ => ::core::unreachable!(),
+                        }
+                    }
+                }
+            });
+        }
+        tokens
     }
 }
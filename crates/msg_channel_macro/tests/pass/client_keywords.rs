@@ -0,0 +1,46 @@
+#![deny(warnings)]
+
+use msg_channel::*;
+
+pub struct Move;
+pub struct Type;
+pub struct Match;
+
+pub struct Board;
+
+impl HandleSync<Move> for Board {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Move) -> Self::Replay {}
+}
+
+impl HandleSync<Type> for Board {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Type) -> Self::Replay {}
+}
+
+impl HandleSyncConcurrent<Match> for Board {
+    type Replay = bool;
+
+    fn handle(&self, _msg: Match) -> Self::Replay {
+        true
+    }
+}
+
+pub struct BoardMsgSet;
+
+#[msg_set(client)]
+impl MessageSet for BoardMsgSet {
+    type Handler = Board;
+    type Async = ();
+    type Sync = (Move, Type);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Match,);
+}
+
+fn main() {
+    let _ = BoardMsgSetClient::r#move;
+    let _ = BoardMsgSetClient::r#type;
+    let _ = BoardMsgSetClient::r#match;
+}
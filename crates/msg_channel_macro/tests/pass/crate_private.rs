@@ -0,0 +1,30 @@
+#![deny(warnings)]
+use msg_channel::*;
+
+pub(crate) struct Ping;
+
+pub(crate) struct Pinger;
+
+impl HandleSync<Ping> for Pinger {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {
+        1
+    }
+}
+
+pub(crate) struct PingMsgSet;
+
+// the attribute can't see that the set is `pub(crate)`, so it is passed on
+#[msg_set(vis = pub(crate))]
+impl MessageSet for PingMsgSet {
+    type Handler = Pinger;
+    type Async = ();
+    type Sync = Ping;
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {
+    let _ = PingMsgSetSyncVariant::Ping(Ping);
+}
@@ -0,0 +1,31 @@
+#![deny(warnings)]
+
+use msg_channel::*;
+
+fn main() {
+    struct Ping;
+
+    struct Pinger;
+
+    impl HandleSync<Ping> for Pinger {
+        type Replay = u32;
+
+        fn handle(&mut self, _msg: Ping) -> Self::Replay {
+            1
+        }
+    }
+
+    struct PingMsgSet;
+
+    #[msg_set(client)]
+    impl MessageSet for PingMsgSet {
+        type Handler = Pinger;
+        type Async = ();
+        type Sync = (Ping,);
+        type AsyncConcurrent = ();
+        type SyncConcurrent = ();
+    }
+
+    let _ = PingMsgSetSyncVariant::Ping(Ping);
+    let _ = PingMsgSetClient::new;
+}
@@ -0,0 +1,51 @@
+#![deny(warnings)]
+
+mod actor {
+    use msg_channel::*;
+
+    struct Ping;
+
+    struct Pinger;
+
+    impl HandleSync<Ping> for Pinger {
+        type Replay = u32;
+
+        fn handle(&mut self, _msg: Ping) -> Self::Replay {
+            1
+        }
+    }
+
+    struct PingMsgSet;
+
+    #[msg_set(vis = , client)]
+    impl MessageSet for PingMsgSet {
+        type Handler = Pinger;
+        type Async = ();
+        type Sync = Ping;
+        type AsyncConcurrent = ();
+        type SyncConcurrent = ();
+    }
+
+    struct Counter;
+
+    #[msg_service(handler = Counter)]
+    trait Count {
+        fn add(&mut self, n: u32) -> u32;
+    }
+
+    impl Count for Counter {
+        fn add(&mut self, n: u32) -> u32 {
+            n
+        }
+    }
+
+    pub fn names() {
+        let _ = PingMsgSetSyncVariant::Ping(Ping);
+        let _ = PingMsgSetClient::new;
+        let _ = CountClient::new;
+    }
+}
+
+fn main() {
+    actor::names();
+}
@@ -0,0 +1,57 @@
+#![deny(warnings)]
+use msg_channel::*;
+
+#[derive(Debug, Clone)]
+pub struct Count(pub u32);
+
+#[derive(Debug, Clone)]
+pub struct Tail;
+
+pub struct Counter;
+
+impl HandleSync<Count> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, msg: Count) -> Self::Replay {
+        msg.0
+    }
+}
+
+impl HandleSync<Tail> for Counter {
+    type Replay = ReplyStream<u32>;
+
+    fn handle(&mut self, _msg: Tail) -> Self::Replay {
+        ReplyStream::channel(1).1
+    }
+}
+
+pub struct CounterMsgSet;
+
+// a `ReplyStream` is not `Debug`, so only the messages are
+#[msg_set(derive(Debug, Clone))]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Async = ();
+    type Sync = (Count, Tail);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct PlainMsgSet;
+
+#[msg_set(derive(Debug, Clone), reply_derive(Debug, Clone, PartialEq))]
+impl MessageSet for PlainMsgSet {
+    type Handler = Counter;
+    type Async = ();
+    type Sync = (Count,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {
+    let item = <CounterMsgSet as MessageSetContains<Tail>>::into_item(Tail);
+    let _ = format!("{:?}", item.clone());
+    let replay = <PlainMsgSet as MessageSetContains<Count>>::into_replay_item(1);
+    assert_eq!(replay.clone(), replay);
+    let _ = format!("{replay:?}");
+}
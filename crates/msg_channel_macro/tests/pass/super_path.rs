@@ -0,0 +1,36 @@
+#![deny(warnings)]
+
+pub mod protocol {
+    pub struct Ping;
+
+    pub mod set {
+        use msg_channel::*;
+
+        pub struct Pinger;
+
+        impl HandleSync<super::Ping> for Pinger {
+            type Replay = u32;
+
+            fn handle(&mut self, _msg: super::Ping) -> Self::Replay {
+                1
+            }
+        }
+
+        pub struct PingMsgSet;
+
+        #[msg_set(client)]
+        impl MessageSet for PingMsgSet {
+            type Handler = Pinger;
+            type Async = ();
+            type Sync = (super::Ping,);
+            type AsyncConcurrent = ();
+            type SyncConcurrent = ();
+        }
+    }
+}
+
+fn main() {
+    // the names of a set without `vis` are as public as before
+    let _ = protocol::set::PingMsgSetSyncVariant::Ping(protocol::Ping);
+    let _ = protocol::set::PingMsgSetClient::new;
+}
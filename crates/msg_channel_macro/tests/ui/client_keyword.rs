@@ -0,0 +1,24 @@
+use msg_channel::*;
+
+pub struct Crate;
+
+pub struct Registry;
+
+impl HandleSync<Crate> for Registry {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Crate) -> Self::Replay {}
+}
+
+pub struct RegistryMsgSet;
+
+#[msg_set(client)]
+impl MessageSet for RegistryMsgSet {
+    type Handler = Registry;
+    type Async = ();
+    type Sync = (Crate,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {}
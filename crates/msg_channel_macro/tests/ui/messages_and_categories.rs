@@ -0,0 +1,26 @@
+use msg_channel::*;
+
+#[derive(Message)]
+#[message(mode = sync)]
+pub struct Ping;
+
+pub struct Pong;
+
+pub struct Pinger;
+
+impl HandleSync<Ping> for Pinger {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {}
+}
+
+pub struct PingMsgSet;
+
+#[msg_set]
+impl MessageSet for PingMsgSet {
+    type Handler = Pinger;
+    type Messages = (Ping,);
+    type Sync = (Pong,);
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use msg_channel::*;
+
+pub struct Ping;
+
+pub struct PingMsgSet;
+
+#[msg_set]
+impl MessageSet for PingMsgSet {
+    type Async = ();
+    type Sync = Ping;
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {}
@@ -0,0 +1,37 @@
+use msg_channel::*;
+
+pub struct Worker;
+
+pub struct Tick;
+pub struct Compute(u64);
+
+impl HandleSyncWithContext<WorkerMsgSet, Tick> for Worker {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Tick, cx: &mut Context<WorkerMsgSet, Self::Replay>) -> Option<()> {
+        // the slot replies to `Tick`, not to `Compute`
+        cx.take_reply()?.send(42u64);
+        None
+    }
+}
+
+impl HandleSync<Compute> for Worker {
+    type Replay = u64;
+
+    fn handle(&mut self, msg: Compute) -> Self::Replay {
+        msg.0
+    }
+}
+
+pub struct WorkerMsgSet;
+
+#[msg_set]
+impl MessageSet for WorkerMsgSet {
+    type Handler = Worker;
+    type Async = ();
+    type Sync = (Tick, Compute);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {}
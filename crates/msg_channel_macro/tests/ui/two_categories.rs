@@ -0,0 +1,30 @@
+use msg_channel::*;
+
+pub struct Ping;
+
+pub struct Pinger;
+
+impl HandleSync<Ping> for Pinger {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {}
+}
+
+impl HandleSyncConcurrent<Ping> for Pinger {
+    type Replay = ();
+
+    fn handle(&self, _msg: Ping) -> Self::Replay {}
+}
+
+pub struct PingMsgSet;
+
+#[msg_set]
+impl MessageSet for PingMsgSet {
+    type Handler = Pinger;
+    type Async = ();
+    type Sync = (Ping,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Ping,);
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use msg_channel::*;
+
+pub struct Pinger;
+
+impl HandleSync<[u8; 4]> for Pinger {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: [u8; 4]) -> Self::Replay {}
+}
+
+pub struct PingMsgSet;
+
+#[msg_set]
+impl MessageSet for PingMsgSet {
+    type Handler = Pinger;
+    type Async = ();
+    type Sync = ([u8; 4],);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {}
@@ -0,0 +1,36 @@
+use msg_channel::*;
+
+mod a {
+    pub struct Ping;
+}
+
+mod b {
+    pub struct Ping;
+}
+
+pub struct Pinger;
+
+impl HandleSync<a::Ping> for Pinger {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: a::Ping) -> Self::Replay {}
+}
+
+impl HandleSync<b::Ping> for Pinger {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: b::Ping) -> Self::Replay {}
+}
+
+pub struct PingMsgSet;
+
+#[msg_set]
+impl MessageSet for PingMsgSet {
+    type Handler = Pinger;
+    type Async = ();
+    type Sync = (a::Ping, b::Ping);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn main() {}
@@ -0,0 +1,100 @@
+use msg_channel::*;
+
+pub struct Ping;
+pub struct Add(u32, u32);
+pub struct Shout(String);
+
+pub struct Math;
+
+impl HandleSync<Ping> for Math {
+    type Replay = &'static str;
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {
+        "math"
+    }
+}
+
+impl HandleSync<Add> for Math {
+    type Replay = u32;
+
+    fn handle(&mut self, msg: Add) -> Self::Replay {
+        msg.0 + msg.1
+    }
+}
+
+pub struct MathMsgSet;
+
+#[msg_set]
+impl MessageSet for MathMsgSet {
+    type Handler = Math;
+    type Async = ();
+    type Sync = (Ping, Add);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Echo;
+
+impl HandleAsync<Ping> for Echo {
+    type Replay = &'static str;
+
+    async fn handle(&mut self, _msg: Ping) -> Self::Replay {
+        "echo"
+    }
+}
+
+impl HandleAsync<Shout> for Echo {
+    type Replay = String;
+
+    async fn handle(&mut self, msg: Shout) -> Self::Replay {
+        msg.0.to_uppercase()
+    }
+}
+
+pub struct EchoMsgSet;
+
+#[msg_set]
+impl MessageSet for EchoMsgSet {
+    type Handler = Echo;
+    type Async = (Ping, Shout);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (math_sender, mut math_receiver) = msg_channel::<MathMsgSet>();
+    let (echo_sender, mut echo_receiver) = msg_channel::<EchoMsgSet>();
+    tokio::spawn(async move {
+        while math_receiver.handle_next(&mut Math).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+    tokio::spawn(async move {
+        while echo_receiver.handle_next(&mut Echo).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let actors: Vec<Box<dyn AnySender>> = vec![Box::new(math_sender), Box::new(echo_sender)];
+    for actor in &actors {
+        let name: &str = actor.request(Ping).await?;
+        match actor.request::<_, u32>(Add(1, 2)).await {
+            Ok(sum) => println!("{name}: 1 + 2 = {sum}"),
+            Err(AnySendError::UnsupportedMessage(_)) => println!("{name}: can't add"),
+            Err(err) => return Err(err.into()),
+        }
+        if let Err(AnySendError::WrongReplyType(_)) =
+            actor.request::<_, u32>(Shout("hi".into())).await
+        {
+            println!("{name}: shout doesn't reply with a number");
+        }
+    }
+
+    // boxed handlers of the same message can share a collection too
+    let mut handlers: Vec<Box<dyn HandleAsyncBoxed<Ping, Replay = &'static str>>> =
+        vec![Box::new(Echo)];
+    for handler in &mut handlers {
+        println!("boxed handler: {}", (**handler).handle_boxed(Ping).await);
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use msg_channel::*;
+
+#[derive(Clone)]
+pub struct UserCreated {
+    name: String,
+    email: Option<String>,
+}
+
+pub struct Audit {
+    entries: Vec<String>,
+}
+
+impl HandleSync<UserCreated> for Audit {
+    type Replay = &'static str;
+
+    fn handle(&mut self, msg: UserCreated) -> Self::Replay {
+        self.entries.push(format!("user {} created", msg.name));
+        "audited"
+    }
+}
+
+pub struct AuditMsgSet;
+
+#[msg_set]
+impl MessageSet for AuditMsgSet {
+    type Handler = Audit;
+    type Async = ();
+    type Sync = (UserCreated,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Mailer;
+
+impl HandleAsync<UserCreated> for Mailer {
+    type Replay = &'static str;
+
+    async fn handle(&mut self, msg: UserCreated) -> Self::Replay {
+        println!("welcome mail to {}", msg.email.unwrap_or_default());
+        "mailed"
+    }
+}
+
+pub struct MailerMsgSet;
+
+#[msg_set]
+impl MessageSet for MailerMsgSet {
+    type Handler = Mailer;
+    type Async = (UserCreated,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let bus = Bus::new();
+
+    let (audit_sender, mut audit_receiver) = msg_channel::<AuditMsgSet>();
+    let (mailer_sender, mut mailer_receiver) = msg_channel::<MailerMsgSet>();
+    let _audit = bus.subscribe::<UserCreated, _>(&audit_sender);
+    let mailer = bus.subscribe_filtered(&mailer_sender, |msg: &UserCreated| msg.email.is_some());
+    tokio::spawn(async move {
+        let mut audit = Audit { entries: vec![] };
+        while audit_receiver.handle_next(&mut audit).await?.is_some() {}
+        println!("audit log: {:?}", audit.entries);
+        Ok::<(), MsgSetRecvError>(())
+    });
+    tokio::spawn(async move {
+        while mailer_receiver.handle_next(&mut Mailer).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let replays = bus
+        .publish_collect::<_, &str>(UserCreated {
+            name: "alice".to_string(),
+            email: Some("alice@example.com".to_string()),
+        })
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    println!("replays: {replays:?}");
+
+    let delivered = bus.publish(UserCreated {
+        name: "bob".to_string(),
+        email: None,
+    });
+    println!("bob delivered to {delivered} subscribers");
+
+    drop(mailer);
+    let replays = bus
+        .publish_collect::<_, &str>(UserCreated {
+            name: "carol".to_string(),
+            email: Some("carol@example.com".to_string()),
+        })
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+    println!("replays after unsubscribing the mailer: {replays:?}");
+
+    drop(audit_sender);
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use msg_channel::*;
+
+pub struct Calculator;
+
+pub struct MsgA;
+pub struct HTTPRequest(String);
+pub struct Square(u32);
+
+impl HandleSync<MsgA> for Calculator {
+    type Replay = &'static str;
+
+    fn handle(&mut self, _msg: MsgA) -> Self::Replay {
+        "a"
+    }
+}
+
+impl HandleAsync<HTTPRequest> for Calculator {
+    type Replay = usize;
+
+    async fn handle(&mut self, msg: HTTPRequest) -> Self::Replay {
+        msg.0.len()
+    }
+}
+
+impl HandleSyncConcurrent<Square> for Calculator {
+    type Replay = u32;
+
+    fn handle(&self, msg: Square) -> Self::Replay {
+        msg.0 * msg.0
+    }
+}
+
+pub struct CalculatorMsgSet;
+
+#[msg_set(client)]
+impl MessageSet for CalculatorMsgSet {
+    type Handler = Calculator;
+    type Async = (HTTPRequest,);
+    type Sync = (MsgA,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Square,);
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<CalculatorMsgSet>();
+    tokio::spawn(async move {
+        while receiver.handle_next(&mut Calculator).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let client = CalculatorMsgSetClient::new(sender);
+    println!("msg_a: {}", client.msg_a(MsgA)?.await);
+    println!("http_request: {}", client.http_request(HTTPRequest("GET /".into()))?.await);
+    println!("square: {}", client.square(Square(7))?.await);
+    Ok(())
+}
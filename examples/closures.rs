@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use msg_channel::*;
+
+#[derive(Message)]
+#[message(reply = u32, mode = sync)]
+pub struct Increment(u32);
+
+#[derive(Message)]
+#[message(reply = u32, mode = sync_concurrent)]
+pub struct Get;
+
+#[derive(Message)]
+#[message(reply = u32, mode = async)]
+pub struct SlowDouble;
+
+#[derive(Default)]
+pub struct Counter {
+    count: u32,
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (mut handler, sender, mut receiver) = FnHandlerBuilder::new(Counter::default())
+        .on_sync(|counter: &mut Counter, msg: Increment| {
+            counter.count += msg.0;
+            counter.count
+        })
+        .on_async(async |counter: &mut Counter, _msg: SlowDouble| {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            counter.count *= 2;
+            counter.count
+        })
+        .on_sync_concurrent(|counter: &Counter, _msg: Get| counter.count)
+        .build_channel()?;
+
+    let client = tokio::spawn(async move {
+        // the replays are typed by the messages
+        println!("incremented: {}", sender.send(Increment(5))?.await);
+        println!("doubled: {}", sender.send(SlowDouble)?.await);
+        let (a, b) = tokio::join!(sender.send(Get)?, sender.send(Get)?);
+        println!("concurrent reads: {a} {b}");
+        Ok::<(), MsgSendError<FnMsgSet<Counter>>>(())
+    });
+
+    while receiver.handle_next(&mut handler).await?.is_some() {}
+    client.await??;
+    println!("final count: {}", handler.state().count);
+    Ok(())
+}
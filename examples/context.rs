@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use msg_channel::*;
+
+pub struct Worker {
+    ticks: u32,
+}
+
+pub struct Start;
+pub struct Tick;
+pub struct Compute(u64);
+
+impl HandleSyncWithContext<WorkerMsgSet, Start> for Worker {
+    type Replay = ();
+
+    fn handle(
+        &mut self,
+        _msg: Start,
+        cx: &mut Context<WorkerMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        cx.send_interval(Duration::from_millis(100), || Tick);
+        Some(())
+    }
+}
+
+impl HandleSyncWithContext<WorkerMsgSet, Tick> for Worker {
+    type Replay = ();
+
+    fn handle(
+        &mut self,
+        _msg: Tick,
+        cx: &mut Context<WorkerMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        self.ticks += 1;
+        println!("tick {}", self.ticks);
+        if self.ticks == 3 {
+            cx.stop();
+        }
+        Some(())
+    }
+}
+
+impl HandleAsyncWithContext<WorkerMsgSet, Compute> for Worker {
+    type Replay = u64;
+
+    async fn handle(
+        &mut self,
+        msg: Compute,
+        cx: &mut Context<WorkerMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        // reply from another task, so the worker can handle the next message right away
+        let reply_to = cx.take_reply()?;
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            reply_to.send(msg.0 * msg.0);
+        });
+        None
+    }
+}
+
+pub struct WorkerMsgSet;
+
+#[msg_set]
+impl MessageSet for WorkerMsgSet {
+    type Handler = Worker;
+    type Async = (Compute,);
+    type Sync = (Start, Tick);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<WorkerMsgSet>();
+    let client = tokio::spawn(async move {
+        sender.send(Start)?.await;
+        println!("computed: {}", sender.send(Compute(12))?.await);
+        // keep the sender alive until the worker stops itself
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    let mut worker = Worker { ticks: 0 };
+    while receiver.handle_next(&mut worker).await?.is_some() {}
+    println!("worker stopped after {} ticks", worker.ticks);
+    client.await??;
+    Ok(())
+}
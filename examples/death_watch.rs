@@ -0,0 +1,81 @@
+use msg_channel::*;
+
+pub struct Worker;
+pub struct Work(u32);
+
+impl HandleSync<Work> for Worker {
+    type Replay = u32;
+
+    fn handle(&mut self, msg: Work) -> Self::Replay {
+        if msg.0 == 0 {
+            panic!("worker can't handle zero");
+        }
+        msg.0 * 2
+    }
+}
+
+pub struct WorkerMsgSet;
+
+#[msg_set]
+impl MessageSet for WorkerMsgSet {
+    type Handler = Worker;
+    type Async = ();
+    type Sync = (Work,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Coordinator {
+    workers: Vec<ChannelId>,
+}
+
+impl HandleSync<Terminated> for Coordinator {
+    type Replay = ();
+
+    fn handle(&mut self, msg: Terminated) -> Self::Replay {
+        println!("worker {:?} terminated: {:?}", msg.id, msg.reason);
+        self.workers.retain(|id| *id != msg.id);
+    }
+}
+
+pub struct CoordinatorMsgSet;
+
+#[msg_set]
+impl MessageSet for CoordinatorMsgSet {
+    type Handler = Coordinator;
+    type Async = ();
+    type Sync = (Terminated,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (coordinator_sender, mut coordinator_receiver) = msg_channel::<CoordinatorMsgSet>();
+    let mut coordinator = Coordinator { workers: vec![] };
+
+    for n in [0, 1] {
+        let (worker_sender, mut worker_receiver) = msg_channel::<WorkerMsgSet>();
+        coordinator_sender.watch(&worker_sender);
+        coordinator.workers.push(worker_sender.id());
+        tokio::spawn(async move {
+            let mut worker = Worker;
+            while worker_receiver.handle_next(&mut worker).await?.is_some() {
+                worker_receiver.stop();
+            }
+            Ok::<(), MsgSetRecvError>(())
+        });
+        drop(worker_sender.send(Work(n))?);
+    }
+
+    while coordinator_receiver
+        .handle_next(&mut coordinator)
+        .await?
+        .is_some()
+    {
+        if coordinator.workers.is_empty() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use msg_channel::*;
+
+#[derive(Message)]
+#[message(reply = u32, mode = async)]
+pub struct Fetch(pub u32);
+
+#[derive(Message)]
+#[message(reply = u32, mode = sync)]
+pub struct Add(pub u32);
+
+#[derive(Message)]
+#[message(reply = u32, mode = sync_concurrent)]
+pub struct Get;
+
+#[derive(Message)]
+#[message(mode = async_concurrent)]
+pub struct Log(pub String);
+
+pub struct Counter {
+    value: u32,
+}
+
+impl HandleAsync<Fetch> for Counter {
+    type Replay = u32;
+
+    async fn handle(&mut self, msg: Fetch) -> Self::Replay {
+        tokio::task::yield_now().await;
+        self.value = msg.0;
+        self.value
+    }
+}
+
+impl HandleSync<Add> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, msg: Add) -> Self::Replay {
+        self.value += msg.0;
+        self.value
+    }
+}
+
+// moving `Get` to another category only changes its `#[message(mode = ...)]` and this impl
+impl HandleSyncConcurrent<Get> for Counter {
+    type Replay = u32;
+
+    fn handle(&self, _msg: Get) -> Self::Replay {
+        self.value
+    }
+}
+
+impl HandleAsyncConcurrent<Log> for Counter {
+    type Replay = ();
+
+    async fn handle(&self, msg: Log) -> Self::Replay {
+        println!("log: {}", msg.0);
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Messages = (Fetch, Add, Get, Log);
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    let client = tokio::spawn(async move {
+        println!("fetch: {}", sender.send(Fetch(10))?.await);
+        println!("add: {}", sender.send(Add(5))?.await);
+        sender.send(Log("counting".to_string()))?.await;
+        println!("get: {}", sender.send(Get)?.await);
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    let mut counter = Counter { value: 0 };
+    while receiver.handle_next(&mut counter).await?.is_some() {}
+    client.await??;
+    Ok(())
+}
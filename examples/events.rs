@@ -0,0 +1,68 @@
+use futures_util::StreamExt;
+
+use msg_channel::*;
+
+pub struct Counter {
+    count: u32,
+}
+
+pub struct Increment;
+
+#[derive(Clone, Debug)]
+pub enum CounterEvent {
+    Changed(u32),
+}
+
+impl HandleSyncWithContext<CounterMsgSet, Increment> for Counter {
+    type Replay = ();
+
+    fn handle(
+        &mut self,
+        _msg: Increment,
+        cx: &mut Context<CounterMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        self.count += 1;
+        cx.publish(CounterEvent::Changed(self.count));
+        Some(())
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Event = CounterEvent;
+    type Async = ();
+    type Sync = (Increment,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    let mut all = sender.subscribe_with(EventPolicy::Unbounded);
+    let mut latest = sender.subscribe_with(EventPolicy::Lagging(2));
+    tokio::spawn(async move {
+        let mut counter = Counter { count: 0 };
+        while receiver.handle_next(&mut counter).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    for _ in 0..5 {
+        sender.send(Increment)?.await;
+    }
+    drop(sender);
+
+    while let Some(event) = all.next().await {
+        println!("all: {:?}", event?);
+    }
+    while let Some(event) = latest.next().await {
+        match event {
+            Ok(event) => println!("latest: {event:?}"),
+            Err(lagged) => println!("latest: {lagged}"),
+        }
+    }
+    Ok(())
+}
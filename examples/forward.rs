@@ -0,0 +1,77 @@
+use msg_channel::*;
+
+pub struct Lookup(String);
+
+pub struct Storage;
+
+impl HandleSync<Lookup> for Storage {
+    type Replay = Option<usize>;
+
+    fn handle(&mut self, msg: Lookup) -> Self::Replay {
+        ["a", "b", "c"].iter().position(|key| *key == msg.0)
+    }
+}
+
+pub struct StorageMsgSet;
+
+#[msg_set]
+impl MessageSet for StorageMsgSet {
+    type Handler = Storage;
+    type Async = ();
+    type Sync = (Lookup,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Router {
+    storage: MessageSetSender<StorageMsgSet>,
+}
+
+impl HandleSyncWithContext<RouterMsgSet, Lookup> for Router {
+    type Replay = Option<usize>;
+
+    fn handle(
+        &mut self,
+        msg: Lookup,
+        cx: &mut Context<RouterMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        // the storage replies to the caller of the router directly
+        let reply_to = cx.take_reply()?;
+        let _ = self.storage.forward(msg, reply_to);
+        None
+    }
+}
+
+pub struct RouterMsgSet;
+
+#[msg_set]
+impl MessageSet for RouterMsgSet {
+    type Handler = Router;
+    type Async = ();
+    type Sync = (Lookup,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (storage_sender, mut storage_receiver) = msg_channel::<StorageMsgSet>();
+    let (router_sender, mut router_receiver) = msg_channel::<RouterMsgSet>();
+    tokio::spawn(async move {
+        while storage_receiver.handle_next(&mut Storage).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+    tokio::spawn(async move {
+        let mut router = Router {
+            storage: storage_sender,
+        };
+        while router_receiver.handle_next(&mut router).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    for key in ["b", "z"] {
+        let index = router_sender.send(Lookup(key.to_string()))?.await;
+        println!("{key}: {index:?}");
+    }
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use std::collections::{BTreeMap, HashMap};
+use std::marker::PhantomData;
+
+use msg_channel::*;
+
+pub trait Backend: Send + Sync + 'static {
+    type Value: Send + Sync + 'static;
+
+    fn get(&self, key: &str) -> Option<&Self::Value>;
+    fn put(&mut self, key: String, value: Self::Value);
+    fn keys(&self) -> Vec<String>;
+}
+
+#[derive(Default)]
+pub struct Memory(HashMap<String, u64>);
+
+impl Backend for Memory {
+    type Value = u64;
+
+    fn get(&self, key: &str) -> Option<&u64> {
+        self.0.get(key)
+    }
+
+    fn put(&mut self, key: String, value: u64) {
+        self.0.insert(key, value);
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.0.keys().cloned().collect()
+    }
+}
+
+#[derive(Default)]
+pub struct Sorted(BTreeMap<String, String>);
+
+impl Backend for Sorted {
+    type Value = String;
+
+    fn get(&self, key: &str) -> Option<&String> {
+        self.0.get(key)
+    }
+
+    fn put(&mut self, key: String, value: String) {
+        self.0.insert(key, value);
+    }
+
+    fn keys(&self) -> Vec<String> {
+        self.0.keys().cloned().collect()
+    }
+}
+
+pub struct Store<B> {
+    backend: B,
+}
+
+pub struct Get(pub String);
+pub struct Put<V>(pub String, pub V);
+pub struct Keys;
+
+impl<B> HandleSyncConcurrent<Get> for Store<B>
+where
+    B: Backend,
+    B::Value: Clone,
+{
+    type Replay = Option<B::Value>;
+
+    fn handle(&self, msg: Get) -> Self::Replay {
+        self.backend.get(&msg.0).cloned()
+    }
+}
+
+impl<B: Backend> HandleSync<Put<B::Value>> for Store<B> {
+    type Replay = ();
+
+    fn handle(&mut self, msg: Put<B::Value>) -> Self::Replay {
+        self.backend.put(msg.0, msg.1)
+    }
+}
+
+impl<B: Backend> HandleSyncConcurrent<Keys> for Store<B> {
+    type Replay = Vec<String>;
+
+    fn handle(&self, _msg: Keys) -> Self::Replay {
+        self.backend.keys()
+    }
+}
+
+pub struct StoreMsgSet<B>(PhantomData<fn() -> B>);
+
+#[msg_set(client)]
+impl<B> MessageSet for StoreMsgSet<B>
+where
+    B: Backend,
+    B::Value: Clone,
+{
+    type Handler = Store<B>;
+    type Async = ();
+    type Sync = (Put<B::Value>,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Get, Keys);
+}
+
+// the same actor, for every backend
+async fn run<B>(backend: B, entries: Vec<(&'static str, B::Value)>) -> color_eyre::Result<()>
+where
+    B: Backend,
+    B::Value: Clone + std::fmt::Debug,
+{
+    let (sender, mut receiver) = msg_channel::<StoreMsgSet<B>>();
+    let client = StoreMsgSetClient::new(sender);
+    let task = tokio::spawn(async move {
+        for (key, value) in entries {
+            client.put(Put(key.to_string(), value))?.await;
+        }
+        println!("keys: {:?}", client.keys(Keys)?.await);
+        println!("get b: {:?}", client.get(Get("b".to_string()))?.await);
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    let mut store = Store { backend };
+    while receiver.handle_next(&mut store).await?.is_some() {}
+    task.await??;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    run(Memory::default(), vec![("a", 1), ("b", 2)]).await?;
+    run(
+        Sorted::default(),
+        vec![("c", "3".to_string()), ("b", "2".to_string())],
+    )
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use msg_channel::*;
+
+#[derive(Clone)]
+pub struct Write {
+    key: String,
+    value: u64,
+}
+
+#[derive(Clone)]
+pub struct Read {
+    key: String,
+}
+
+pub struct Replica {
+    data: HashMap<String, u64>,
+    delay: Duration,
+}
+
+impl HandleAsync<Write> for Replica {
+    type Replay = ();
+
+    async fn handle(&mut self, msg: Write) -> Self::Replay {
+        tokio::time::sleep(self.delay).await;
+        self.data.insert(msg.key, msg.value);
+    }
+}
+
+impl HandleAsync<Read> for Replica {
+    type Replay = Option<u64>;
+
+    async fn handle(&mut self, msg: Read) -> Self::Replay {
+        tokio::time::sleep(self.delay).await;
+        self.data.get(&msg.key).copied()
+    }
+}
+
+pub struct ReplicaMsgSet;
+
+#[msg_set]
+impl MessageSet for ReplicaMsgSet {
+    type Handler = Replica;
+    type Async = (Write, Read);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let mut writes = Group::new();
+    let mut reads = Group::new();
+    for delay in [10, 20, 500] {
+        let (sender, mut receiver) = msg_channel::<ReplicaMsgSet>();
+        writes.add(&sender);
+        reads.add(&sender);
+        tokio::spawn(async move {
+            let mut replica = Replica {
+                data: HashMap::new(),
+                delay: Duration::from_millis(delay),
+            };
+            while receiver.handle_next(&mut replica).await?.is_some() {}
+            Ok::<(), MsgSetRecvError>(())
+        });
+    }
+
+    let write = Write {
+        key: "a".to_string(),
+        value: 1,
+    };
+    writes.broadcast(write).majority().await?;
+    println!("written to a majority");
+
+    let read = Read {
+        key: "a".to_string(),
+    };
+    println!("quorum read: {:?}", reads.broadcast(read.clone()).quorum(2).await?);
+    println!("first read: {:?}", reads.broadcast(read.clone()).first(1).await);
+    let all = reads
+        .broadcast(read)
+        .timeout(Duration::from_millis(100))
+        .all()
+        .await;
+    println!("all reads within 100ms: {all:?}");
+    Ok(())
+}
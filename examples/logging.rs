@@ -0,0 +1,107 @@
+use msg_channel::*;
+
+#[derive(Message, Debug, Clone, PartialEq)]
+#[message(reply = u32, mode = sync)]
+pub struct Add(pub u32);
+
+#[derive(Message, Debug, Clone, PartialEq)]
+#[message(reply = u32, mode = async_concurrent)]
+pub struct Get;
+
+pub struct Counter {
+    value: u32,
+}
+
+impl HandleSync<Add> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, msg: Add) -> Self::Replay {
+        self.value += msg.0;
+        self.value
+    }
+}
+
+impl HandleAsyncConcurrent<Get> for Counter {
+    type Replay = u32;
+
+    async fn handle(&self, _msg: Get) -> Self::Replay {
+        self.value
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set(derive(Debug, Clone, PartialEq), reply_derive(Debug))]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Messages = (Add, Get);
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+pub struct Files;
+
+impl HandleSync<Rename> for Files {
+    type Replay = bool;
+
+    fn handle(&mut self, msg: Rename) -> Self::Replay {
+        msg.from != msg.to
+    }
+}
+
+pub struct FilesMsgSet;
+
+#[msg_set(derive(Debug, serde::Serialize))]
+impl MessageSet for FilesMsgSet {
+    type Handler = Files;
+    type Async = ();
+    type Sync = Rename;
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    let client = tokio::spawn(async move {
+        sender.send(Add(1))?.await;
+        sender.send(Add(2))?.await;
+        println!("get: {}", sender.send(Get)?.await);
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    // logs every message and its replay before the handler sees them
+    let mut counter = Counter { value: 0 };
+    let mut last = None;
+    while let Some((item, reply_to)) = receiver.recv().await {
+        println!(
+            "received {item:?}, same as the last one: {}",
+            last.as_ref() == Some(&item)
+        );
+        last = Some(item.clone());
+        let reply_to = reply_to.map(|replay: MessageSetReplayItem<CounterMsgSet>| {
+            println!("replied {replay:?}");
+            replay
+        });
+        receiver.handle_msg(&mut counter, (item, reply_to)).await?;
+    }
+    client.await??;
+
+    let (sender, mut receiver) = msg_channel::<FilesMsgSet>();
+    sender.send(Rename {
+        from: "a.txt".to_string(),
+        to: "b.txt".to_string(),
+    })?;
+    drop(sender);
+    while let Some((item, reply_to)) = receiver.recv().await {
+        if let MessageSetItem::Sync(variant) = &item {
+            println!("received {}", serde_json::to_string(variant)?);
+        }
+        receiver.handle_msg(&mut Files, (item, reply_to)).await?;
+    }
+    Ok(())
+}
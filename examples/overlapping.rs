@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use msg_channel::*;
+
+// one handler type behind two sets: the full admin set and a read-only public one
+#[derive(Clone, Default)]
+pub struct Registry {
+    entries: Arc<RwLock<HashMap<String, String>>>,
+}
+
+pub struct Get(pub String);
+pub struct Put(pub String, pub String);
+pub struct Remove(pub String);
+
+impl HandleSyncConcurrent<Get> for Registry {
+    type Replay = Option<String>;
+
+    fn handle(&self, msg: Get) -> Self::Replay {
+        self.entries.read().unwrap().get(&msg.0).cloned()
+    }
+}
+
+impl HandleSync<Put> for Registry {
+    type Replay = ();
+
+    fn handle(&mut self, msg: Put) -> Self::Replay {
+        self.entries.write().unwrap().insert(msg.0, msg.1);
+    }
+}
+
+impl HandleSync<Remove> for Registry {
+    type Replay = bool;
+
+    fn handle(&mut self, msg: Remove) -> Self::Replay {
+        self.entries.write().unwrap().remove(&msg.0).is_some()
+    }
+}
+
+pub struct AdminMsgSet;
+
+#[msg_set]
+impl MessageSet for AdminMsgSet {
+    type Handler = Registry;
+    type Async = ();
+    type Sync = (Put, Remove);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Get,);
+}
+
+pub struct PublicMsgSet;
+
+#[msg_set]
+impl MessageSet for PublicMsgSet {
+    type Handler = Registry;
+    type Async = ();
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Get,);
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let registry = Registry::default();
+    let (admin, mut admin_receiver) = msg_channel::<AdminMsgSet>();
+    let (public, mut public_receiver) = msg_channel::<PublicMsgSet>();
+    let mut admin_registry = registry.clone();
+    let mut public_registry = registry;
+    tokio::spawn(async move {
+        while let Ok(Some(_)) = admin_receiver.handle_next(&mut admin_registry).await {}
+    });
+    tokio::spawn(async move {
+        while let Ok(Some(_)) = public_receiver.handle_next(&mut public_registry).await {}
+    });
+
+    admin
+        .send(Put("motd".to_string(), "hello".to_string()))?
+        .await;
+    println!(
+        "public get: {:?}",
+        public.send(Get("motd".to_string()))?.await
+    );
+    // `public.send(Remove(..))` doesn't compile, `PublicMsgSet` has no `Remove`
+    println!(
+        "admin remove: {}",
+        admin.send(Remove("motd".to_string()))?.await
+    );
+    println!(
+        "public get: {:?}",
+        public.send(Get("motd".to_string()))?.await
+    );
+    Ok(())
+}
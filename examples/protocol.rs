@@ -0,0 +1,80 @@
+use msg_channel::*;
+
+// stands in for a shared crate of messages
+mod protocol {
+    pub mod health {
+        pub struct Ping;
+    }
+
+    pub mod chat {
+        pub struct Ping(pub String);
+        pub struct Say(pub String);
+    }
+}
+
+pub struct Server {
+    said: Vec<String>,
+}
+
+impl HandleSyncConcurrent<protocol::health::Ping> for Server {
+    type Replay = &'static str;
+
+    fn handle(&self, _msg: protocol::health::Ping) -> Self::Replay {
+        "healthy"
+    }
+}
+
+impl HandleSyncConcurrent<protocol::chat::Ping> for Server {
+    type Replay = String;
+
+    fn handle(&self, msg: protocol::chat::Ping) -> Self::Replay {
+        format!("pong from {}", msg.0)
+    }
+}
+
+impl HandleAsync<protocol::chat::Say> for Server {
+    type Replay = usize;
+
+    async fn handle(&mut self, msg: protocol::chat::Say) -> Self::Replay {
+        self.said.push(msg.0);
+        self.said.len()
+    }
+}
+
+pub struct ServerMsgSet;
+
+#[msg_set(client)]
+impl MessageSet for ServerMsgSet {
+    type Handler = Server;
+    type Async = (protocol::chat::Say,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    // both messages are named `Ping`, so one of them needs another variant name
+    #[variant(protocol::chat::Ping = ChatPing)]
+    type SyncConcurrent = (protocol::health::Ping, protocol::chat::Ping);
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<ServerMsgSet>();
+    let client = ServerMsgSetClient::new(sender);
+    let task = tokio::spawn(async move {
+        println!("health: {}", client.ping(protocol::health::Ping)?.await);
+        println!(
+            "chat: {}",
+            client
+                .chat_ping(protocol::chat::Ping("alice".to_string()))?
+                .await
+        );
+        println!(
+            "said: {}",
+            client.say(protocol::chat::Say("hi".to_string()))?.await
+        );
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    let mut server = Server { said: Vec::new() };
+    while receiver.handle_next(&mut server).await?.is_some() {}
+    task.await??;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use msg_channel::*;
+
+pub struct Report(String);
+
+pub struct Logger;
+
+impl HandleSync<Report> for Logger {
+    type Replay = bool;
+
+    fn handle(&mut self, msg: Report) -> Self::Replay {
+        println!("log: {}", msg.0);
+        true
+    }
+}
+
+pub struct LoggerMsgSet;
+
+#[msg_set]
+impl MessageSet for LoggerMsgSet {
+    type Handler = Logger;
+    type Async = ();
+    type Sync = (Report,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Metrics {
+    reports: u32,
+}
+
+impl HandleAsync<Report> for Metrics {
+    type Replay = bool;
+
+    async fn handle(&mut self, _msg: Report) -> Self::Replay {
+        self.reports += 1;
+        println!("metrics: {} reports", self.reports);
+        self.reports < 2
+    }
+}
+
+pub struct MetricsMsgSet;
+
+#[msg_set]
+impl MessageSet for MetricsMsgSet {
+    type Handler = Metrics;
+    type Async = (Report,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+/// Knows nothing about the sets it reports to.
+pub struct Service {
+    reporters: Vec<Recipient<Report, bool>>,
+}
+
+impl Service {
+    async fn run(&self, job: &str) -> Result<(), RecipientSendError> {
+        for reporter in &self.reporters {
+            let accepted = reporter.send(Report(format!("{job} done")))?.await;
+            println!("{:?} accepted: {accepted}", reporter.id());
+        }
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (logger_sender, mut logger_receiver) = msg_channel::<LoggerMsgSet>();
+    let (metrics_sender, mut metrics_receiver) = msg_channel::<MetricsMsgSet>();
+    tokio::spawn(async move {
+        while logger_receiver.handle_next(&mut Logger).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+    tokio::spawn(async move {
+        let mut metrics = Metrics { reports: 0 };
+        while metrics_receiver.handle_next(&mut metrics).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let service = Service {
+        reporters: vec![logger_sender.recipient(), metrics_sender.into()],
+    };
+    service.run("build").await?;
+    service.run("deploy").await?;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// no `use msg_channel::*`: the generated code names everything by path
+
+// stands in for a framework crate wrapping msg_channel
+mod framework {
+    pub use msg_channel as channel;
+}
+
+use framework::channel::{msg_set, HandleSync, Message, MessageSet};
+
+pub struct Counter(u32);
+
+pub struct Increment;
+
+#[derive(Message)]
+#[message(crate = framework::channel, reply = u32, mode = sync)]
+pub struct Get;
+
+impl HandleSync<Increment> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Increment) -> Self::Replay {
+        self.0 += 1;
+        self.0
+    }
+}
+
+impl HandleSync<Get> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Get) -> Self::Replay {
+        self.0
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set(client, crate = framework::channel)]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Async = ();
+    type Sync = (Increment, Get);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct FlatMsgSet;
+
+// without `crate = ...`, the code refers to `::msg_channel`
+#[msg_set]
+impl MessageSet for FlatMsgSet {
+    type Handler = Counter;
+    type Messages = (Get,);
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = framework::channel::msg_channel::<CounterMsgSet>();
+    let client = CounterMsgSetClient::new(sender);
+    let task = tokio::spawn(async move {
+        client.increment(Increment)?.await;
+        println!("count: {}", client.get(Get)?.await);
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    let mut counter = Counter(0);
+    while receiver.handle_next(&mut counter).await?.is_some() {}
+    task.await??;
+
+    let (sender, mut receiver) = framework::channel::msg_channel::<FlatMsgSet>();
+    let replay = sender.send(Get)?;
+    drop(sender);
+    while receiver.handle_next(&mut counter).await?.is_some() {}
+    println!("count: {}", replay.await);
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use msg_channel::*;
+
+pub struct Greet(String);
+pub struct Add(i64, i64);
+pub struct Unknown;
+
+pub struct Greeter {
+    greeted: u32,
+}
+
+impl HandleSync<Greet> for Greeter {
+    type Replay = String;
+
+    fn handle(&mut self, msg: Greet) -> Self::Replay {
+        self.greeted += 1;
+        format!("hello {} (#{})", msg.0, self.greeted)
+    }
+}
+
+pub struct Calculator;
+
+impl HandleAsync<Add> for Calculator {
+    type Replay = i64;
+
+    async fn handle(&mut self, msg: Add) -> Self::Replay {
+        msg.0 + msg.1
+    }
+}
+
+// plugins only known at runtime register their handlers
+fn load_plugins(names: &[&str]) -> RouterBuilder {
+    names
+        .iter()
+        .fold(RouterBuilder::new(), |builder, name| match *name {
+            "greeter" => builder.handler(Greeter { greeted: 0 }, |routes| {
+                routes.handle_sync::<Greet>();
+            }),
+            "calculator" => builder.handler(Calculator, |routes| {
+                routes.handle_async::<Add>();
+            }),
+            _ => builder,
+        })
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    // two plugins can't handle the same message
+    if let Err(err) = load_plugins(&["greeter", "greeter"]).build() {
+        println!("{err}");
+    }
+
+    let (sender, receiver) = load_plugins(&["greeter", "calculator"]).build()?;
+    let client = tokio::spawn(async move {
+        let greeting: String = sender.request(Greet("alice".into())).await?;
+        println!("{greeting}");
+        let sum: i64 = sender.request(Add(2, 3)).await?;
+        println!("2 + 3 = {sum}");
+        match sender.request::<_, ()>(Unknown).await {
+            Err(RouterError::Unhandled(_)) => println!("no plugin handles Unknown"),
+            other => println!("unexpected: {other:?}"),
+        }
+        Ok::<(), RouterError>(())
+    });
+
+    // the router runs on the main task, its futures are not `Send`
+    receiver.run().await;
+    client.await??;
+    Ok(())
+}
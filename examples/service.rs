@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use msg_channel::*;
+
+pub struct MemoryKv {
+    map: HashMap<String, String>,
+}
+
+#[msg_service(handler = MemoryKv)]
+pub trait Kv {
+    async fn get(&mut self, key: String) -> Option<String>;
+    fn set(&mut self, key: String, value: String);
+    fn count(&self) -> usize;
+    async fn contains_key(&self, key: String) -> bool;
+}
+
+impl Kv for MemoryKv {
+    async fn get(&mut self, key: String) -> Option<String> {
+        self.map.get(&key).cloned()
+    }
+
+    fn set(&mut self, key: String, value: String) {
+        self.map.insert(key, value);
+    }
+
+    fn count(&self) -> usize {
+        self.map.len()
+    }
+
+    async fn contains_key(&self, key: String) -> bool {
+        self.map.contains_key(&key)
+    }
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<KvMsgSet>();
+    tokio::spawn(async move {
+        let mut kv = MemoryKv {
+            map: HashMap::new(),
+        };
+        while receiver.handle_next(&mut kv).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let client = KvClient::new(sender);
+    client.set("a".to_string(), "1".to_string()).await?;
+    println!("get a: {:?}", client.get("a".to_string()).await?);
+    println!("contains b: {}", client.contains_key("b".to_string()).await?);
+    println!("count: {}", client.count().await?);
+
+    // the generated messages can be sent directly as well
+    let value = client.sender.send(KvGet { key: "a".to_string() })?.await;
+    println!("sent KvGet: {value:?}");
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use msg_channel::*;
+
+pub struct Repl {
+    vars: HashMap<String, i64>,
+}
+
+pub struct OpenRepl {
+    session: Session<String, String>,
+}
+
+impl HandleAsync<OpenRepl> for Repl {
+    type Replay = usize;
+
+    async fn handle(&mut self, mut msg: OpenRepl) -> Self::Replay {
+        let mut lines = 0;
+        while let Some(line) = msg.session.recv().await {
+            lines += 1;
+            let output = match line.split_once('=') {
+                Some((name, value)) => match value.trim().parse() {
+                    Ok(value) => {
+                        self.vars.insert(name.trim().to_string(), value);
+                        "ok".to_string()
+                    }
+                    Err(err) => err.to_string(),
+                },
+                None => match self.vars.get(line.trim()) {
+                    Some(value) => value.to_string(),
+                    None => format!("unknown variable {}", line.trim()),
+                },
+            };
+            if msg.session.send(output).await.is_err() {
+                break;
+            }
+        }
+        lines
+    }
+}
+
+pub struct ReplMsgSet;
+
+#[msg_set]
+impl MessageSet for ReplMsgSet {
+    type Handler = Repl;
+    type Async = (OpenRepl,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<ReplMsgSet>();
+    tokio::spawn(async move {
+        let mut repl = Repl {
+            vars: HashMap::new(),
+        };
+        while receiver.handle_next(&mut repl).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let (mut session, replay) = sender.open_session(1, |session| OpenRepl { session })?;
+    for line in ["x = 1", "y = 2", "x", "z"] {
+        session.send(line.to_string()).await?;
+        println!("> {line}\n{}", session.recv().await.unwrap_or_default());
+    }
+    drop(session);
+    println!("session handled {} lines", replay.await);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use futures_util::{stream, StreamExt};
+
+use msg_channel::*;
+
+pub struct Database {
+    rows: Vec<String>,
+}
+
+pub struct Query {
+    prefix: String,
+}
+
+pub struct Count(u32);
+
+impl HandleAsync<Query> for Database {
+    type Replay = ReplyStream<String>;
+
+    async fn handle(&mut self, msg: Query) -> Self::Replay {
+        let rows: Vec<_> = self
+            .rows
+            .iter()
+            .filter(|row| row.starts_with(&msg.prefix))
+            .cloned()
+            .collect();
+        let (sink, rows_stream) = ReplyStream::channel(2);
+        tokio::spawn(async move {
+            for row in rows {
+                // fails once the caller dropped the stream
+                if sink.send(row).await.is_err() {
+                    println!("query cancelled");
+                    return;
+                }
+            }
+        });
+        rows_stream
+    }
+}
+
+impl HandleSync<Count> for Database {
+    type Replay = ReplyStream<u32>;
+
+    fn handle(&mut self, msg: Count) -> Self::Replay {
+        ReplyStream::from_stream(stream::iter(0..msg.0))
+    }
+}
+
+pub struct DatabaseMsgSet;
+
+#[msg_set]
+impl MessageSet for DatabaseMsgSet {
+    type Handler = Database;
+    type Async = (Query,);
+    type Sync = (Count,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<DatabaseMsgSet>();
+    tokio::spawn(async move {
+        let mut database = Database {
+            rows: (0..100).map(|n| format!("row {n}")).collect(),
+        };
+        while receiver.handle_next(&mut database).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let mut rows = sender.send(Query {
+        prefix: "row 1".to_string(),
+    })?;
+    while let Some(row) = rows.next().await {
+        println!("{row}");
+    }
+
+    let first: Vec<_> = sender
+        .send(Query {
+            prefix: "row".to_string(),
+        })?
+        .take(3)
+        .collect()
+        .await;
+    println!("first rows: {first:?}");
+
+    let counted: Vec<_> = sender.send(Count(5))?.collect().await;
+    println!("counted: {counted:?}");
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    Ok(())
+}
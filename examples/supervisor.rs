@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use msg_channel::*;
+
+pub struct Counter {
+    count: u32,
+}
+
+pub struct Increment;
+pub struct Crash;
+
+impl HandleSync<Increment> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Increment) -> Self::Replay {
+        self.count += 1;
+        self.count
+    }
+}
+
+impl HandleSync<Crash> for Counter {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Crash) -> Self::Replay {
+        panic!("counter crashed");
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Async = ();
+    type Sync = (Increment, Crash);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let mut supervisor = Supervisor::new(RestartStrategy::OneForOne)
+        .restart_intensity(3, Duration::from_secs(1))
+        .backoff(Backoff::Fixed(Duration::from_millis(100)));
+    let sender = supervisor.supervise::<CounterMsgSet, _>(|| Counter { count: 0 });
+
+    let client = tokio::spawn(async move {
+        println!("count: {}", sender.send(Increment)?.await);
+        println!("count: {}", sender.send(Increment)?.await);
+        // the reply of the crashed message is lost, but the sender stays valid
+        drop(sender.send(Crash)?);
+        println!("count after restart: {}", sender.send(Increment)?.await);
+        Ok::<(), color_eyre::Report>(())
+    });
+
+    supervisor.run().await?;
+    client.await??;
+    Ok(())
+}
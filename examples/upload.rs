@@ -0,0 +1,54 @@
+use futures_util::StreamExt;
+
+use msg_channel::*;
+
+pub struct Store;
+
+pub struct Upload {
+    name: String,
+    chunks: RequestStream<Vec<u8>>,
+}
+
+impl HandleAsync<Upload> for Store {
+    type Replay = usize;
+
+    async fn handle(&mut self, mut msg: Upload) -> Self::Replay {
+        let mut len = 0;
+        while let Some(chunk) = msg.chunks.next().await {
+            len += chunk.len();
+        }
+        println!("stored {}", msg.name);
+        len
+    }
+}
+
+pub struct StoreMsgSet;
+
+#[msg_set]
+impl MessageSet for StoreMsgSet {
+    type Handler = Store;
+    type Async = (Upload,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::main]
+async fn main() -> color_eyre::Result<()> {
+    let (sender, mut receiver) = msg_channel::<StoreMsgSet>();
+    tokio::spawn(async move {
+        while receiver.handle_next(&mut Store).await?.is_some() {}
+        Ok::<(), MsgSetRecvError>(())
+    });
+
+    let (sink, replay) = sender.send_stream(4, |chunks| Upload {
+        name: "file.bin".to_string(),
+        chunks,
+    })?;
+    for _ in 0..16 {
+        sink.send(vec![0; 1024]).await?;
+    }
+    sink.finish();
+    println!("uploaded {} bytes", replay.await);
+    Ok(())
+}
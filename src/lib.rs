@@ -1,13 +1,27 @@
+pub use any::*;
+pub use bus::*;
+pub use context::*;
+pub use event::*;
+pub use fn_handler::*;
+pub use group::*;
 pub use handle::*;
+pub use lifecycle::*;
+pub use message::{mode, Message, MessageMode};
 pub use message_set::*;
-use msg_channel_core::{handle,message_set};
+pub use recipient::*;
+pub use reply::*;
+pub use request::*;
+pub use session::*;
+pub use router::*;
+pub use supervisor::*;
+pub use timer::*;
+use msg_channel_core::{any,bus,context,event,fn_handler,group,handle,lifecycle,message,message_set,recipient,reply,request,router,session,supervisor,timer};
 pub use msg_channel_core::msg_channel;
-pub use msg_channel_macro::msg_set;
+pub use msg_channel_macro::{msg_service, msg_set, Message};
 
 pub mod internal {
-    pub use msg_channel_core::{
-        define_msg_variant, impl_concurrent_msg_handle_for_variant, impl_msg_handle_for_variant,
-        impl_sync_concurrent_msg_handle_for_variant, impl_sync_msg_handle_for_variant,
+    pub use msg_channel_core::message::{
+        Active, AsyncConcurrentSlot, AsyncSlot, Inactive, ModeSlot, SlotOf, SyncConcurrentSlot,
+        SyncSlot,
     };
-    pub use paste::paste;
 }
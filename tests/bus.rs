@@ -0,0 +1,117 @@
+use msg_channel::*;
+
+#[derive(Clone)]
+pub struct Ping;
+
+#[derive(Clone)]
+pub struct Value(u32);
+
+pub struct Counter;
+
+impl HandleSync<Ping> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {
+        1
+    }
+}
+
+impl HandleSync<Value> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, msg: Value) -> Self::Replay {
+        msg.0
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Async = ();
+    type Sync = (Ping, Value);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::test]
+async fn collects_replies() {
+    let bus = Bus::new();
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    let _subscription = bus.subscribe::<Ping, _>(&sender);
+    tokio::spawn(
+        async move { while let Ok(Some(_)) = receiver.handle_next(&mut Counter).await {} },
+    );
+
+    let replays = bus.publish_collect::<_, u32>(Ping).await;
+    assert_eq!(replays.len(), 1);
+    assert_eq!(*replays[0].as_ref().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn wrong_reply_types_are_errors() {
+    let bus = Bus::new();
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    let _subscription = bus.subscribe::<Ping, _>(&sender);
+    tokio::spawn(
+        async move { while let Ok(Some(_)) = receiver.handle_next(&mut Counter).await {} },
+    );
+
+    let replays = bus.publish_collect::<_, String>(Ping).await;
+    assert_eq!(replays.len(), 1);
+    match replays.into_iter().next().unwrap() {
+        Err(BusReplyError::WrongReplyType(replay)) => {
+            assert_eq!(replay.downcast::<u32>().unwrap(), 1)
+        }
+        other => panic!("expected a wrong reply type, got {other:?}"),
+    }
+}
+
+fn counter() -> MessageSetSender<CounterMsgSet> {
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    tokio::spawn(
+        async move { while let Ok(Some(_)) = receiver.handle_next(&mut Counter).await {} },
+    );
+    sender
+}
+
+#[tokio::test]
+async fn filtered_subscribers_only_get_matching_messages() {
+    let bus = Bus::new();
+    let all = counter();
+    let even = counter();
+    let _all = bus.subscribe::<Value, _>(&all);
+    let _even = bus.subscribe_filtered(&even, |msg: &Value| msg.0.is_multiple_of(2));
+
+    let replays = bus.publish_collect::<_, u32>(Value(1)).await;
+    assert_eq!(
+        replays.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+        [1]
+    );
+    let replays = bus.publish_collect::<_, u32>(Value(2)).await;
+    assert_eq!(
+        replays.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+        [2, 2]
+    );
+    assert_eq!(bus.publish(Value(3)), 1);
+}
+
+#[tokio::test]
+async fn dropped_subscriptions_unsubscribe() {
+    let bus = Bus::new();
+    let sender = counter();
+    let ping = bus.subscribe::<Ping, _>(&sender);
+    let value = bus.subscribe::<Value, _>(&sender);
+    assert_eq!(bus.publish(Ping), 1);
+
+    drop(ping);
+    assert_eq!(bus.publish(Ping), 0);
+    assert!(bus.publish_collect::<_, u32>(Ping).await.is_empty());
+    // the other subscriptions of the sender stay
+    assert_eq!(bus.publish(Value(1)), 1);
+
+    // the subscription outliving the bus is fine
+    drop(bus);
+    drop(value);
+}
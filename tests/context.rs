@@ -0,0 +1,122 @@
+use msg_channel::*;
+
+pub struct Lookup(u32);
+
+pub struct Storage;
+
+impl HandleSync<Lookup> for Storage {
+    type Replay = Option<u32>;
+
+    fn handle(&mut self, msg: Lookup) -> Self::Replay {
+        Some(msg.0 * 2)
+    }
+}
+
+pub struct StorageMsgSet;
+
+#[msg_set]
+impl MessageSet for StorageMsgSet {
+    type Handler = Storage;
+    type Async = ();
+    type Sync = (Lookup,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Gateway {
+    storage: MessageSetSender<StorageMsgSet>,
+}
+
+pub struct Square(u32);
+
+impl HandleSyncWithContext<GatewayMsgSet, Lookup> for Gateway {
+    type Replay = Option<u32>;
+
+    fn handle(
+        &mut self,
+        msg: Lookup,
+        cx: &mut Context<GatewayMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        let reply_to = cx.take_reply()?;
+        self.storage.forward(msg, reply_to).unwrap();
+        None
+    }
+}
+
+impl HandleAsyncConcurrentWithContext<GatewayMsgSet, Square> for Gateway {
+    type Replay = u32;
+
+    async fn handle(
+        &self,
+        msg: Square,
+        cx: &mut Context<GatewayMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        let reply_to = cx.take_reply()?;
+        tokio::spawn(async move { reply_to.send(msg.0 * msg.0) });
+        None
+    }
+}
+
+pub struct GatewayMsgSet;
+
+#[msg_set]
+impl MessageSet for GatewayMsgSet {
+    type Handler = Gateway;
+    type Async = ();
+    type Sync = (Lookup,);
+    type AsyncConcurrent = (Square,);
+    type SyncConcurrent = ();
+}
+
+#[derive(Message)]
+#[message(reply = u32, mode = async)]
+pub struct Later(u32);
+
+pub struct Deferred;
+
+impl HandleAsyncWithContext<DeferredMsgSet, Later> for Deferred {
+    type Replay = u32;
+
+    async fn handle(
+        &mut self,
+        msg: Later,
+        cx: &mut Context<DeferredMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        cx.reply(msg.0);
+        None
+    }
+}
+
+pub struct DeferredMsgSet;
+
+#[msg_set]
+impl MessageSet for DeferredMsgSet {
+    type Handler = Deferred;
+    type Messages = (Later,);
+}
+
+#[tokio::test]
+async fn forwarded_replies_reach_the_caller() {
+    let (storage, mut storage_receiver) = msg_channel::<StorageMsgSet>();
+    let (gateway, mut gateway_receiver) = msg_channel::<GatewayMsgSet>();
+    tokio::spawn(async move {
+        while let Ok(Some(_)) = storage_receiver.handle_next(&mut Storage).await {}
+    });
+    tokio::spawn(async move {
+        let mut handler = Gateway { storage };
+        while let Ok(Some(_)) = gateway_receiver.handle_next(&mut handler).await {}
+    });
+
+    assert_eq!(gateway.send(Lookup(21)).unwrap().await, Some(42));
+    assert_eq!(gateway.send(Square(3)).unwrap().await, 9);
+}
+
+#[tokio::test]
+async fn replies_from_the_context_of_a_mode_set() {
+    let (sender, mut receiver) = msg_channel::<DeferredMsgSet>();
+    tokio::spawn(
+        async move { while let Ok(Some(_)) = receiver.handle_next(&mut Deferred).await {} },
+    );
+
+    assert_eq!(sender.send(Later(7)).unwrap().await, 7);
+}
@@ -0,0 +1,131 @@
+use futures_util::StreamExt;
+
+use msg_channel::*;
+
+pub struct Counter {
+    count: u32,
+}
+
+pub struct Increment;
+pub struct Stop;
+
+impl HandleSyncWithContext<CounterMsgSet, Increment> for Counter {
+    /// The number of subscribers the event was delivered to.
+    type Replay = usize;
+
+    fn handle(
+        &mut self,
+        _msg: Increment,
+        cx: &mut Context<CounterMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        self.count += 1;
+        Some(cx.publish(self.count))
+    }
+}
+
+impl HandleSyncWithContext<CounterMsgSet, Stop> for Counter {
+    type Replay = ();
+
+    fn handle(
+        &mut self,
+        _msg: Stop,
+        cx: &mut Context<CounterMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        cx.stop();
+        Some(())
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Event = u32;
+    type Async = ();
+    type Sync = (Increment, Stop);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn counter() -> MessageSetSender<CounterMsgSet> {
+    let (sender, mut receiver) = msg_channel::<CounterMsgSet>();
+    tokio::spawn(async move {
+        let mut counter = Counter { count: 0 };
+        while let Ok(Some(_)) = receiver.handle_next(&mut counter).await {}
+    });
+    sender
+}
+
+async fn increment(sender: &MessageSetSender<CounterMsgSet>, times: usize) {
+    for _ in 0..times {
+        sender.send(Increment).unwrap().await;
+    }
+}
+
+#[tokio::test]
+async fn lagging_subscribers_skip_the_oldest_events() {
+    let sender = counter();
+    let mut events = sender.subscribe_with(EventPolicy::Lagging(2));
+
+    increment(&sender, 5).await;
+    assert_eq!(events.next().await, Some(Err(EventLagged(3))));
+    assert_eq!(events.next().await, Some(Ok(4)));
+    assert_eq!(events.next().await, Some(Ok(5)));
+
+    // the count starts over once it was received
+    increment(&sender, 3).await;
+    assert_eq!(events.next().await, Some(Err(EventLagged(1))));
+    assert_eq!(events.next().await, Some(Ok(7)));
+    assert_eq!(events.next().await, Some(Ok(8)));
+}
+
+#[tokio::test]
+async fn lagging_keeps_at_least_one_event() {
+    let sender = counter();
+    let mut events = sender.subscribe_with(EventPolicy::Lagging(0));
+
+    increment(&sender, 2).await;
+    assert_eq!(events.next().await, Some(Err(EventLagged(1))));
+    assert_eq!(events.next().await, Some(Ok(2)));
+}
+
+#[tokio::test]
+async fn unbounded_subscribers_keep_every_event() {
+    let sender = counter();
+    let mut all = sender.subscribe_with(EventPolicy::Unbounded);
+    let mut default = sender.subscribe();
+
+    increment(&sender, 200).await;
+    sender.send(Stop).unwrap().await;
+    let events: Vec<_> = all.by_ref().collect().await;
+    assert_eq!(events, (1..=200).map(Ok).collect::<Vec<_>>());
+    assert_eq!(default.next().await, Some(Err(EventLagged(72))));
+}
+
+#[tokio::test]
+async fn streams_end_once_the_receiver_terminated() {
+    let sender = counter();
+    let mut events = sender.subscribe();
+
+    increment(&sender, 2).await;
+    sender.send(Stop).unwrap().await;
+    // the buffered events are received before the end
+    assert_eq!(events.next().await, Some(Ok(1)));
+    assert_eq!(events.next().await, Some(Ok(2)));
+    assert_eq!(events.next().await, None);
+
+    // later subscribers end right away
+    assert_eq!(sender.subscribe().next().await, None);
+}
+
+#[tokio::test]
+async fn dropped_subscribers_are_not_delivered_to() {
+    let sender = counter();
+    let events = sender.subscribe();
+    let _other = sender.subscribe();
+
+    assert_eq!(sender.send(Increment).unwrap().await, 2);
+    drop(events);
+    assert_eq!(sender.send(Increment).unwrap().await, 1);
+}
@@ -0,0 +1,125 @@
+use std::thread::ThreadId;
+use std::time::Duration;
+
+use msg_channel::*;
+
+#[derive(Message)]
+#[message(reply = u32, mode = sync)]
+pub struct Add(u32);
+
+#[derive(Message)]
+#[message(reply = u32, mode = async)]
+pub struct Double;
+
+#[derive(Message)]
+#[message(reply = ThreadId, mode = sync_concurrent)]
+pub struct WhichThread;
+
+#[derive(Message)]
+#[message(reply = u32, mode = async_concurrent)]
+pub struct Get;
+
+#[derive(Message)]
+#[message(mode = sync)]
+pub struct Unregistered;
+
+#[derive(Message)]
+#[message(mode = sync)]
+pub struct Crash;
+
+fn counter() -> FnHandler<u32> {
+    FnHandlerBuilder::new(0u32)
+        .on_sync(|count: &mut u32, msg: Add| {
+            *count += msg.0;
+            *count
+        })
+        .on_async(async |count: &mut u32, _msg: Double| {
+            tokio::task::yield_now().await;
+            *count *= 2;
+            *count
+        })
+        .on_sync_concurrent(|_: &u32, _msg: WhichThread| std::thread::current().id())
+        .on_async_concurrent(async |count: &u32, _msg: Get| *count)
+        .on_sync(|_: &mut u32, _msg: Crash| panic!("crash"))
+        .build()
+        .unwrap()
+}
+
+async fn run(mut handler: FnHandler<u32>, mut receiver: MessageSetReceiver<FnMsgSet<u32>>) -> u32 {
+    while receiver.handle_next(&mut handler).await.unwrap().is_some() {}
+    handler.into_state()
+}
+
+#[tokio::test]
+async fn typed_replies() {
+    let (sender, receiver) = msg_channel::<FnMsgSet<u32>>();
+    let client = async move {
+        let added: u32 = sender.send(Add(3)).unwrap().await;
+        assert_eq!(added, 3);
+        assert_eq!(sender.send(Double).unwrap().await, 6);
+        let (a, b) = tokio::join!(sender.send(Get).unwrap(), sender.send(Get).unwrap());
+        assert_eq!((a, b), (6, 6));
+        // sync concurrent closures run on the blocking thread pool
+        let thread = sender.send(WhichThread).unwrap().await;
+        assert_ne!(thread, std::thread::current().id());
+        assert!(sender.send(Unregistered).unwrap().try_replay().await.is_err());
+    };
+    let (count, ()) = tokio::join!(run(counter(), receiver), client);
+    assert_eq!(count, 6);
+}
+
+#[test]
+fn messages_are_registered_once() {
+    let err = FnHandlerBuilder::new(0u32)
+        .on_sync(|count: &mut u32, _msg: Add| *count)
+        .on_sync(|count: &mut u32, msg: Add| *count + msg.0)
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.message, std::any::type_name::<Add>());
+}
+
+#[tokio::test(start_paused = true)]
+async fn timers_and_recipients() {
+    let (sender, receiver) = msg_channel::<FnMsgSet<u32>>();
+    let recipient: Recipient<Add, u32> = sender.recipient();
+    let client = async move {
+        sender.send_after(Duration::from_secs(1), Add(1));
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert_eq!(recipient.send(Add(1)).unwrap().await, 2);
+    };
+    let (count, ()) = tokio::join!(run(counter(), receiver), client);
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn watch() {
+    let (watcher, mut watcher_receiver) = msg_channel::<FnMsgSet<Vec<TerminationReason>>>();
+    let mut watcher_handler = FnHandlerBuilder::new(vec![])
+        .on_sync(|reasons: &mut Vec<TerminationReason>, msg: Terminated| reasons.push(msg.reason))
+        .build()
+        .unwrap();
+    let (watched, receiver) = msg_channel::<FnMsgSet<u32>>();
+    watcher.watch(&watched);
+    drop(watched);
+    run(counter(), receiver).await;
+    watcher_receiver
+        .handle_next(&mut watcher_handler)
+        .await
+        .unwrap();
+    assert_eq!(watcher_handler.state(), &[TerminationReason::Closed]);
+}
+
+#[tokio::test]
+async fn supervised() {
+    let mut supervisor = Supervisor::new(RestartStrategy::OneForOne);
+    let sender = supervisor.supervise::<FnMsgSet<u32>, _>(counter);
+    let client = async move {
+        assert_eq!(sender.send(Add(2)).unwrap().await, 2);
+        assert!(sender.send(Crash).unwrap().try_replay().await.is_err());
+        // the restarted handler starts from a new state
+        assert_eq!(sender.send(Add(2)).unwrap().await, 2);
+    };
+    let (result, ()) = tokio::join!(supervisor.run(), client);
+    result.unwrap();
+}
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use msg_channel::*;
+use tokio::time::Instant;
+
+#[derive(Clone)]
+pub struct Vote;
+
+#[derive(Clone, Copy)]
+pub enum Behavior {
+    /// Replies with its index after the delay.
+    Reply(u64),
+    /// Stops without replying after the delay.
+    Stop(u64),
+    /// Never replies.
+    Stall,
+}
+
+pub struct Node {
+    index: usize,
+    behavior: Behavior,
+}
+
+impl HandleAsyncWithContext<NodeMsgSet, Vote> for Node {
+    type Replay = usize;
+
+    async fn handle(
+        &mut self,
+        _msg: Vote,
+        cx: &mut Context<NodeMsgSet, Self::Replay>,
+    ) -> Option<Self::Replay> {
+        match self.behavior {
+            Behavior::Reply(delay) => {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                Some(self.index)
+            }
+            Behavior::Stop(delay) => {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                cx.stop();
+                None
+            }
+            Behavior::Stall => std::future::pending().await,
+        }
+    }
+}
+
+pub struct NodeMsgSet;
+
+#[msg_set]
+impl MessageSet for NodeMsgSet {
+    type Handler = Node;
+    type Async = (Vote,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+/// A group of one running node per behavior, `None` is a node that stopped before the broadcast.
+fn group(behaviors: &[Option<Behavior>]) -> Group<Vote, usize> {
+    let mut group = Group::new();
+    for (index, behavior) in behaviors.iter().enumerate() {
+        let (sender, mut receiver) = msg_channel::<NodeMsgSet>();
+        group.add(&sender);
+        if let Some(behavior) = *behavior {
+            tokio::spawn(async move {
+                let mut handler = Node { index, behavior };
+                while let Ok(Some(_)) = receiver.handle_next(&mut handler).await {}
+            });
+        }
+    }
+    group
+}
+
+#[tokio::test(start_paused = true)]
+async fn all_reports_every_member_in_order() {
+    let group = group(&[
+        Some(Behavior::Reply(10)),
+        Some(Behavior::Stop(5)),
+        Some(Behavior::Reply(1)),
+        None,
+    ]);
+    assert_eq!(
+        group.broadcast(Vote).all().await,
+        [
+            Ok(0),
+            Err(BroadcastError::Unavailable),
+            Ok(2),
+            Err(BroadcastError::Unavailable)
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn missing_replays_time_out() {
+    let group = group(&[Some(Behavior::Reply(10)), Some(Behavior::Stall)]);
+    let start = Instant::now();
+    let replays = group
+        .broadcast(Vote)
+        .timeout(Duration::from_secs(1))
+        .all()
+        .await;
+    assert_eq!(replays, [Ok(0), Err(BroadcastError::TimedOut)]);
+    assert_eq!(start.elapsed(), Duration::from_secs(1));
+}
+
+#[tokio::test(start_paused = true)]
+async fn first_skips_failures_in_arrival_order() {
+    let group = group(&[
+        Some(Behavior::Reply(30)),
+        Some(Behavior::Stop(1)),
+        Some(Behavior::Reply(10)),
+        Some(Behavior::Reply(20)),
+        None,
+    ]);
+    assert_eq!(group.broadcast(Vote).first(2).await, [2, 3]);
+    // fewer once every member replied or failed
+    assert_eq!(group.broadcast(Vote).first(5).await, [2, 3, 0]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn first_stops_at_the_deadline() {
+    let group = group(&[Some(Behavior::Reply(10)), Some(Behavior::Stall)]);
+    let replays = group
+        .broadcast(Vote)
+        .timeout(Duration::from_secs(1))
+        .first(2)
+        .await;
+    assert_eq!(replays, [0]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn quorum_is_reached_without_waiting_for_the_rest() {
+    let group = group(&[
+        Some(Behavior::Reply(20)),
+        Some(Behavior::Stall),
+        Some(Behavior::Reply(10)),
+    ]);
+    let start = Instant::now();
+    assert_eq!(group.broadcast(Vote).quorum(2).await, Ok(vec![2, 0]));
+    assert_eq!(start.elapsed(), Duration::from_millis(20));
+}
+
+#[tokio::test(start_paused = true)]
+async fn quorum_fails_once_it_is_out_of_reach() {
+    let group = group(&[
+        Some(Behavior::Stop(1)),
+        Some(Behavior::Reply(1)),
+        Some(Behavior::Stop(2)),
+        Some(Behavior::Stall),
+    ]);
+    let start = Instant::now();
+    // the stalled member can't make up for the two that stopped
+    assert_eq!(
+        group.broadcast(Vote).quorum(3).await,
+        Err(BroadcastError::QuorumNotReached {
+            replays: 1,
+            quorum: 3
+        })
+    );
+    assert_eq!(start.elapsed(), Duration::from_millis(2));
+}
+
+#[tokio::test(start_paused = true)]
+async fn members_unavailable_at_send_count_against_the_quorum() {
+    let group = group(&[None, Some(Behavior::Stall), Some(Behavior::Stall)]);
+    let start = Instant::now();
+    assert_eq!(
+        group.broadcast(Vote).quorum(3).await,
+        Err(BroadcastError::QuorumNotReached {
+            replays: 0,
+            quorum: 3
+        })
+    );
+    assert_eq!(start.elapsed(), Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn quorum_times_out() {
+    let group = group(&[
+        Some(Behavior::Reply(1)),
+        Some(Behavior::Stall),
+        Some(Behavior::Stall),
+    ]);
+    let replays = group
+        .broadcast(Vote)
+        .timeout(Duration::from_secs(1))
+        .quorum(2)
+        .await;
+    assert_eq!(replays, Err(BroadcastError::TimedOut));
+}
+
+#[tokio::test(start_paused = true)]
+async fn majority_needs_more_than_half() {
+    let odd = group(&[
+        Some(Behavior::Reply(3)),
+        Some(Behavior::Stall),
+        Some(Behavior::Reply(1)),
+        Some(Behavior::Stall),
+        Some(Behavior::Reply(2)),
+    ]);
+    assert_eq!(odd.broadcast(Vote).majority().await, Ok(vec![2, 4, 0]));
+
+    // half of an even group is not a majority
+    let even = group(&[
+        Some(Behavior::Reply(1)),
+        Some(Behavior::Stop(2)),
+        Some(Behavior::Reply(1)),
+        Some(Behavior::Stop(2)),
+    ]);
+    assert_eq!(
+        even.broadcast(Vote).majority().await,
+        Err(BroadcastError::QuorumNotReached {
+            replays: 2,
+            quorum: 3
+        })
+    );
+}
@@ -0,0 +1,55 @@
+use std::any::TypeId;
+
+use msg_channel::*;
+
+pub struct Ping;
+
+pub struct First;
+
+impl HandleSync<Ping> for First {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {
+        1
+    }
+}
+
+pub struct Second;
+
+impl HandleSync<Ping> for Second {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Ping) -> Self::Replay {
+        2
+    }
+}
+
+#[test]
+fn duplicate_routes_fail_to_build() {
+    let builder = RouterBuilder::new().handler(First, |routes| {
+        routes.handle_sync::<Ping>();
+    });
+    assert!(builder.is_routed(TypeId::of::<Ping>()));
+    let err = builder
+        .handler(Second, |routes| {
+            routes.handle_sync::<Ping>();
+        })
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.message, std::any::type_name::<Ping>());
+}
+
+#[tokio::test]
+async fn routes_by_message_type() {
+    let (sender, receiver) = RouterBuilder::new()
+        .handler(First, |routes| {
+            routes.handle_sync::<Ping>();
+        })
+        .build()
+        .unwrap();
+    // the router stops once the sender is dropped
+    let replay = async move { sender.request::<_, u32>(Ping).await };
+    let (replay, ()) = tokio::join!(replay, receiver.run());
+    assert_eq!(replay.unwrap(), 1);
+}
@@ -0,0 +1,90 @@
+use futures_util::StreamExt;
+use msg_channel::*;
+
+#[tokio::test]
+async fn reply_streams_buffer_at_least_one_item() {
+    let (sink, stream) = ReplyStream::channel(0);
+    tokio::spawn(async move {
+        for i in 0..3 {
+            sink.send(i).await.unwrap();
+        }
+    });
+    assert_eq!(stream.collect::<Vec<u32>>().await, [0, 1, 2]);
+}
+
+pub struct Upload(RequestStream<u32>);
+
+pub struct Store;
+
+impl HandleAsync<Upload> for Store {
+    type Replay = u32;
+
+    async fn handle(&mut self, msg: Upload) -> Self::Replay {
+        msg.0.fold(0, |sum, chunk| async move { sum + chunk }).await
+    }
+}
+
+pub struct StoreMsgSet;
+
+#[msg_set]
+impl MessageSet for StoreMsgSet {
+    type Handler = Store;
+    type Async = (Upload,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::test]
+async fn request_streams_buffer_at_least_one_chunk() {
+    let (sender, mut receiver) = msg_channel::<StoreMsgSet>();
+    tokio::spawn(async move { while let Ok(Some(_)) = receiver.handle_next(&mut Store).await {} });
+
+    let (sink, replay) = sender.send_stream(0, Upload).unwrap();
+    for chunk in 1..=3 {
+        sink.send(chunk).await.unwrap();
+    }
+    sink.finish();
+    assert_eq!(replay.await, 6);
+}
+
+pub struct Echo(Session<u32, u32>);
+
+pub struct Echoer;
+
+impl HandleAsync<Echo> for Echoer {
+    type Replay = ();
+
+    async fn handle(&mut self, mut msg: Echo) -> Self::Replay {
+        while let Some(n) = msg.0.recv().await {
+            if msg.0.send(n * 10).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+pub struct EchoMsgSet;
+
+#[msg_set]
+impl MessageSet for EchoMsgSet {
+    type Handler = Echoer;
+    type Async = (Echo,);
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+#[tokio::test]
+async fn sessions_buffer_at_least_one_message() {
+    let (sender, mut receiver) = msg_channel::<EchoMsgSet>();
+    tokio::spawn(async move { while let Ok(Some(_)) = receiver.handle_next(&mut Echoer).await {} });
+
+    let (mut session, replay) = sender.open_session(0, Echo).unwrap();
+    for n in 1..=3 {
+        session.send(n).await.unwrap();
+        assert_eq!(session.recv().await, Some(n * 10));
+    }
+    drop(session);
+    replay.await;
+}
@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use msg_channel::*;
+use tokio::time::Instant;
+
+pub struct Counter {
+    count: u32,
+}
+
+pub struct Increment;
+pub struct Crash;
+/// Replies with the count after a second.
+pub struct Slow;
+
+impl HandleSync<Increment> for Counter {
+    type Replay = u32;
+
+    fn handle(&mut self, _msg: Increment) -> Self::Replay {
+        self.count += 1;
+        self.count
+    }
+}
+
+impl HandleSync<Crash> for Counter {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Crash) -> Self::Replay {
+        panic!("counter crashed");
+    }
+}
+
+impl HandleAsync<Slow> for Counter {
+    type Replay = u32;
+
+    async fn handle(&mut self, _msg: Slow) -> Self::Replay {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        self.count
+    }
+}
+
+pub struct CounterMsgSet;
+
+#[msg_set]
+impl MessageSet for CounterMsgSet {
+    type Handler = Counter;
+    type Async = (Slow,);
+    type Sync = (Increment, Crash);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+pub struct Watcher {
+    terminated: Vec<Terminated>,
+}
+
+impl HandleSync<Terminated> for Watcher {
+    type Replay = ();
+
+    fn handle(&mut self, msg: Terminated) -> Self::Replay {
+        self.terminated.push(msg);
+    }
+}
+
+pub struct WatcherMsgSet;
+
+#[msg_set]
+impl MessageSet for WatcherMsgSet {
+    type Handler = Watcher;
+    type Async = ();
+    type Sync = (Terminated,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+fn counter() -> Counter {
+    Counter { count: 0 }
+}
+
+#[tokio::test(start_paused = true)]
+async fn one_for_one_restarts_the_failed_handler() {
+    let mut supervisor = Supervisor::new(RestartStrategy::OneForOne);
+    let a = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let b = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let client = async move {
+        assert_eq!(a.send(Increment).unwrap().await, 1);
+        assert_eq!(b.send(Increment).unwrap().await, 1);
+        drop(a.send(Crash).unwrap());
+        assert_eq!(a.send(Increment).unwrap().await, 1);
+        assert_eq!(b.send(Increment).unwrap().await, 2);
+    };
+    let (result, ()) = tokio::join!(supervisor.run(), client);
+    result.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn one_for_all_restarts_siblings_between_messages() {
+    let mut supervisor = Supervisor::new(RestartStrategy::OneForAll);
+    let a = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let b = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let client = async move {
+        assert_eq!(b.send(Increment).unwrap().await, 1);
+        let slow = b.send(Slow).unwrap();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(a.send(Crash).unwrap());
+        // the sibling finishes the message it is handling before it is restarted
+        assert_eq!(slow.await, 1);
+        assert_eq!(b.send(Increment).unwrap().await, 1);
+        assert_eq!(a.send(Increment).unwrap().await, 1);
+    };
+    let (result, ()) = tokio::join!(supervisor.run(), client);
+    result.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn gives_up_at_the_restart_intensity() {
+    let mut supervisor =
+        Supervisor::new(RestartStrategy::OneForOne).restart_intensity(2, Duration::from_secs(10));
+    let a = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let client = async move {
+        for _ in 0..2 {
+            assert_eq!(a.send(Increment).unwrap().await, 1);
+            drop(a.send(Crash).unwrap());
+        }
+        assert_eq!(a.send(Increment).unwrap().await, 1);
+        drop(a.send(Crash).unwrap());
+        // the third failure is one restart too many, the queued message is dropped with the child
+        assert!(a.send(Increment).unwrap().try_replay().await.is_err());
+    };
+    let (result, ()) = tokio::join!(supervisor.run(), client);
+    assert!(matches!(
+        result,
+        Err(SupervisorError::RestartIntensityExceeded(
+            ChildFailure::Panicked(_)
+        ))
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn restarts_outside_the_window_are_forgotten() {
+    let mut supervisor =
+        Supervisor::new(RestartStrategy::OneForOne).restart_intensity(1, Duration::from_secs(1));
+    let a = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let client = async move {
+        for _ in 0..3 {
+            drop(a.send(Crash).unwrap());
+            assert_eq!(a.send(Increment).unwrap().await, 1);
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    };
+    let (result, ()) = tokio::join!(supervisor.run(), client);
+    result.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn exponential_backoff_doubles_up_to_the_max() {
+    let mut supervisor = Supervisor::new(RestartStrategy::OneForOne)
+        .restart_intensity(5, Duration::from_secs(60))
+        .backoff(Backoff::Exponential {
+            base: Duration::from_millis(100),
+            max: Duration::from_millis(300),
+        });
+    let a = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let client = async move {
+        for delay in [100, 200, 300] {
+            let start = Instant::now();
+            drop(a.send(Crash).unwrap());
+            assert_eq!(a.send(Increment).unwrap().await, 1);
+            let elapsed = start.elapsed();
+            let delay = Duration::from_millis(delay);
+            assert!(
+                elapsed >= delay && elapsed < delay + Duration::from_millis(5),
+                "restarted after {elapsed:?} instead of {delay:?}"
+            );
+        }
+    };
+    let (result, ()) = tokio::join!(supervisor.run(), client);
+    result.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn watchers_learn_why_children_stopped_once_it_gives_up() {
+    let mut supervisor =
+        Supervisor::new(RestartStrategy::OneForOne).restart_intensity(0, Duration::from_secs(1));
+    let a = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let b = supervisor.supervise::<CounterMsgSet, _>(counter);
+    let (watcher, mut watcher_receiver) = msg_channel::<WatcherMsgSet>();
+    watcher.watch(&a);
+    watcher.watch(&b);
+
+    drop(a.send(Crash).unwrap());
+    assert!(supervisor.run().await.is_err());
+
+    let mut handler = Watcher { terminated: vec![] };
+    for _ in 0..2 {
+        watcher_receiver.handle_next(&mut handler).await.unwrap();
+    }
+    let reasons: Vec<_> = handler
+        .terminated
+        .iter()
+        .map(|terminated| (terminated.id, terminated.reason.clone()))
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            (a.id(), TerminationReason::Panicked),
+            (b.id(), TerminationReason::Stopped)
+        ]
+    );
+}
+
+pub struct Blocking;
+
+pub struct Worker {
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl HandleSyncConcurrent<Blocking> for Worker {
+    type Replay = ();
+
+    fn handle(&self, _msg: Blocking) -> Self::Replay {
+        std::thread::sleep(Duration::from_millis(100));
+        self.log.lock().unwrap().push("handled");
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.log.lock().unwrap().push("dropped");
+    }
+}
+
+pub struct WorkerMsgSet;
+
+#[msg_set]
+impl MessageSet for WorkerMsgSet {
+    type Handler = Worker;
+    type Async = ();
+    type Sync = ();
+    type AsyncConcurrent = ();
+    type SyncConcurrent = (Blocking,);
+}
+
+#[tokio::test]
+async fn cancelled_run_keeps_the_handler_of_blocking_handlers() {
+    let log = Arc::new(Mutex::new(vec![]));
+    let mut supervisor = Supervisor::new(RestartStrategy::OneForOne);
+    let sender = supervisor.supervise::<WorkerMsgSet, _>({
+        let log = log.clone();
+        move || Worker { log: log.clone() }
+    });
+    let replay = sender.send(Blocking).unwrap();
+
+    let run = tokio::time::timeout(Duration::from_millis(20), supervisor.run()).await;
+    assert!(run.is_err());
+    replay.try_replay().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(*log.lock().unwrap(), ["handled", "dropped"]);
+}
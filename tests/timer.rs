@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use msg_channel::*;
+use tokio::time::{timeout, Instant};
+
+pub struct Ticks(u32);
+
+pub struct Tick;
+
+impl HandleSync<Tick> for Ticks {
+    type Replay = ();
+
+    fn handle(&mut self, _msg: Tick) -> Self::Replay {
+        self.0 += 1;
+    }
+}
+
+pub struct TicksMsgSet;
+
+#[msg_set]
+impl MessageSet for TicksMsgSet {
+    type Handler = Ticks;
+    type Async = ();
+    type Sync = (Tick,);
+    type AsyncConcurrent = ();
+    type SyncConcurrent = ();
+}
+
+// handles the next message, returns how long it took to arrive
+async fn next_after(receiver: &mut MessageSetReceiver<TicksMsgSet>, ticks: &mut Ticks) -> Duration {
+    let start = Instant::now();
+    receiver.handle_next(ticks).await.unwrap().unwrap();
+    start.elapsed()
+}
+
+#[tokio::test(start_paused = true)]
+async fn send_after() {
+    let (sender, mut receiver) = msg_channel::<TicksMsgSet>();
+    let mut ticks = Ticks(0);
+    sender.send_after(Duration::from_millis(250), Tick);
+    assert_eq!(next_after(&mut receiver, &mut ticks).await, Duration::from_millis(250));
+    assert_eq!(ticks.0, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn send_at() {
+    let (sender, mut receiver) = msg_channel::<TicksMsgSet>();
+    let mut ticks = Ticks(0);
+    sender.send_at(Instant::now() + Duration::from_secs(90), Tick);
+    sender.send_at(Instant::now() + Duration::from_secs(30), Tick);
+    assert_eq!(next_after(&mut receiver, &mut ticks).await, Duration::from_secs(30));
+    assert_eq!(next_after(&mut receiver, &mut ticks).await, Duration::from_secs(60));
+    // a deadline in the past is sent right away
+    sender.send_at(Instant::now() - Duration::from_secs(1), Tick);
+    assert_eq!(next_after(&mut receiver, &mut ticks).await, Duration::ZERO);
+}
+
+#[tokio::test(start_paused = true)]
+async fn send_interval() {
+    let (sender, mut receiver) = msg_channel::<TicksMsgSet>();
+    let mut ticks = Ticks(0);
+    let timer = sender.send_interval(Duration::from_millis(100), || Tick);
+    for _ in 0..3 {
+        assert_eq!(next_after(&mut receiver, &mut ticks).await, Duration::from_millis(100));
+    }
+    timer.cancel();
+    assert!(timer.is_cancelled());
+    assert!(timeout(Duration::from_secs(1), receiver.recv()).await.is_err());
+    assert_eq!(ticks.0, 3);
+}
+
+#[tokio::test(start_paused = true)]
+async fn cancel() {
+    let (sender, mut receiver) = msg_channel::<TicksMsgSet>();
+    let mut ticks = Ticks(0);
+    let cancelled = sender.send_after(Duration::from_millis(100), Tick);
+    sender.send_after(Duration::from_millis(200), Tick);
+    cancelled.cancel();
+    assert_eq!(next_after(&mut receiver, &mut ticks).await, Duration::from_millis(200));
+    assert!(timeout(Duration::from_secs(1), receiver.recv()).await.is_err());
+    assert_eq!(ticks.0, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn long_delays_do_not_panic() {
+    let (sender, mut receiver) = msg_channel::<TicksMsgSet>();
+    let after = sender.send_after(Duration::MAX, Tick);
+    let interval = sender.send_interval(Duration::MAX, || Tick);
+    assert!(timeout(Duration::from_secs(3600), receiver.recv()).await.is_err());
+    after.cancel();
+    interval.cancel();
+}